@@ -0,0 +1,185 @@
+/// Step-by-step precedence explanations for Lox expressions.
+///
+/// Walks an `Expr` with a visitor and reprints it with every operation
+/// parenthesized, then lists the operations in the order they apply,
+/// innermost first, each with its level from `PRECEDENCE_TABLE`. Meant for
+/// teaching how the parser grouped an expression.
+use crate::expr::{Expr, LiteralValue, Visitor};
+use crate::formatter::literal_source;
+use crate::parser::{precedence, PRECEDENCE_TABLE};
+use crate::token::{Token, TokenType};
+
+/// Visitor that parenthesizes an expression and records each operation.
+///
+/// Each visit method returns the fully parenthesized text of its node.
+/// Groupings from the source are dropped, since every operation already
+/// gets its own parentheses.
+#[derive(Default)]
+pub struct Explainer {
+    /// Parenthesized text of each operation and a description of its
+    /// operator, in the order the operations apply
+    steps: Vec<(String, String)>,
+}
+
+impl Explainer {
+    /// Creates a new explainer with no recorded steps.
+    ///
+    /// # Returns
+    /// New Explainer instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Explains how an expression groups.
+    ///
+    /// # Arguments
+    /// * `expr` - Root of the expression tree to explain
+    ///
+    /// # Returns
+    /// The parenthesized expression on the first line, followed by one
+    /// numbered line per operation
+    pub fn explain(&mut self, expr: &Expr) -> String {
+        self.steps.clear();
+        let mut output = expr.accept(self);
+        output.push('\n');
+
+        let width = self.steps.iter().map(|(text, _)| text.chars().count()).max().unwrap_or(0);
+        for (number, (text, operator)) in self.steps.iter().enumerate() {
+            output.push_str(&format!("{:>3}. {:<width$}  {}\n", number + 1, text, operator, width = width));
+        }
+        output
+    }
+
+    /// Records an operation with an operator from the precedence table.
+    ///
+    /// # Arguments
+    /// * `text` - Parenthesized text of the operation
+    /// * `symbol` - Operator as written
+    /// * `operator` - Token type used to look up the operator's level
+    ///
+    /// # Returns
+    /// The operation's text, for the parent to embed
+    fn step(&mut self, text: String, symbol: &str, operator: TokenType) -> String {
+        let description = match precedence(&operator) {
+            Some(level) => {
+                let row = &PRECEDENCE_TABLE[usize::from(level) - 1];
+                format!("'{}' {}, level {} of {}", symbol, row.name, level, PRECEDENCE_TABLE.len())
+            }
+            None => format!("'{}'", symbol),
+        };
+        self.steps.push((text.clone(), description));
+        text
+    }
+
+    /// Records an operation that isn't in the precedence table.
+    ///
+    /// # Arguments
+    /// * `text` - Parenthesized text of the operation
+    /// * `description` - What kind of operation it is
+    ///
+    /// # Returns
+    /// The operation's text, for the parent to embed
+    fn other_step(&mut self, text: String, description: &str) -> String {
+        self.steps.push((text.clone(), description.to_string()));
+        text
+    }
+
+    /// Parenthesizes a comma-separated list of expressions.
+    ///
+    /// # Arguments
+    /// * `exprs` - Expressions to render, in order
+    ///
+    /// # Returns
+    /// The rendered expressions joined with `, `
+    fn list(&mut self, exprs: &[Expr]) -> String {
+        let parts: Vec<String> = exprs.iter().map(|expr| expr.accept(self)).collect();
+        parts.join(", ")
+    }
+}
+
+impl Visitor<String> for Explainer {
+    fn visit_binary(&mut self, left: &Expr, operator: &Token, right: &Expr) -> String {
+        let left = left.accept(self);
+        let right = right.accept(self);
+        let text = match operator.token_type {
+            TokenType::Comma => format!("({}, {})", left, right),
+            _ => format!("({} {} {})", left, operator.lexeme, right),
+        };
+        self.step(text, &operator.lexeme, operator.token_type)
+    }
+
+    fn visit_grouping(&mut self, expression: &Expr) -> String {
+        expression.accept(self)
+    }
+
+    fn visit_literal(&mut self, value: &LiteralValue) -> String {
+        literal_source(value)
+    }
+
+    fn visit_unary(&mut self, operator: &Token, right: &Expr) -> String {
+        let right = right.accept(self);
+        let text = format!("({}{})", operator.lexeme, right);
+        self.other_step(
+            text,
+            &format!("prefix '{}', binds tighter than every level but power", operator.lexeme),
+        )
+    }
+
+    fn visit_ternary(&mut self, condition: &Expr, then_branch: &Expr, else_branch: &Expr) -> String {
+        let condition = condition.accept(self);
+        let then_branch = then_branch.accept(self);
+        let else_branch = else_branch.accept(self);
+        let text = format!("({} ? {} : {})", condition, then_branch, else_branch);
+        self.step(text, "?:", TokenType::Question)
+    }
+
+    fn visit_array(&mut self, elements: &[Expr]) -> String {
+        format!("[{}]", self.list(elements))
+    }
+
+    fn visit_index(&mut self, object: &Expr, _bracket: &Token, index: &Expr) -> String {
+        let text = format!("{}[{}]", object.accept(self), index.accept(self));
+        self.other_step(text, "index, binds tightest")
+    }
+
+    fn visit_map(&mut self, entries: &[(Expr, Expr)]) -> String {
+        let parts: Vec<String> = entries
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key.accept(self), value.accept(self)))
+            .collect();
+        format!("{{{}}}", parts.join(", "))
+    }
+
+    fn visit_range(&mut self, start: &Expr, end: &Expr) -> String {
+        let text = format!("({}..{})", start.accept(self), end.accept(self));
+        self.step(text, "..", TokenType::DotDot)
+    }
+}
+
+/// Explains how an expression groups and the order its operations apply.
+///
+/// # Arguments
+/// * `expr` - Expression tree to explain
+///
+/// # Returns
+/// The parenthesized expression followed by one numbered line per
+/// operation, innermost first
+///
+/// # Examples
+/// ```
+/// use jaloxc::explain::explain;
+/// use jaloxc::parser::Parser;
+/// use jaloxc::scanner::Scanner;
+///
+/// let tokens = Scanner::new("2 + 3 * 4").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+///
+/// assert_eq!(explain(&expr), "\
+/// (2 + (3 * 4))
+///   1. (3 * 4)        '*' factor, level 11 of 12
+///   2. (2 + (3 * 4))  '+' term, level 10 of 12
+/// ");
+/// ```
+pub fn explain(expr: &Expr) -> String {
+    Explainer::new().explain(expr)
+}
@@ -1,208 +1,779 @@
 /// Defines the abstract syntax tree (AST) for the Lox language expressions.
 ///
-/// This module implements the Visitor pattern for traversing expression trees.
-/// Expressions include literals, unary operations, binary operations, and grouping.
+/// This module implements the Visitor pattern for traversing expression trees,
+/// with `Visitor` for infallible passes and `TryVisitor` for passes that can
+/// fail.
+/// Expressions include literals, unary operations, binary operations, grouping,
+/// the ternary conditional, array and map literals, indexing, and ranges.
+///
+/// The enum, both visitor traits, the `accept` dispatch and the constructors
+/// are generated by `define_ast!` from one list of variants, so adding an
+/// expression means adding a single entry there (plus a `Display` arm).
 ///
 /// # Examples
 /// ```
-/// use jaloxc::ast::{Expr, LiteralValue};
+/// use jaloxc::expr::{Expr, LiteralValue};
 /// use jaloxc::token::Token;
 /// use jaloxc::token::TokenType::*;
 ///
 /// // Create a literal expression: 42
-/// let literal = Expr::Literal(LiteralValue::Number(42.0));
+/// let literal = Expr::literal(LiteralValue::Number(42.0));
 ///
 /// // Create a unary expression: -42
 /// let unary = Expr::Unary {
-///     operator: Token::new(Minus, "-".to_string(), None, 1),
+///     operator: Token::new(Minus, "-".to_string(), None, 1, 1),
 ///     right: Box::new(literal),
 /// };
 ///
 /// // Create a grouping expression: (-42)
-/// let grouping = Expr::Grouping(Box::new(unary));
+/// let grouping = Expr::grouping(unary);
+/// ```
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::token::Token;
+
+/// Generates an AST enum together with its visitor machinery.
+///
+/// Each variant is written as
+///
+/// ```text
+/// /// Variant docs
+/// Name(constructor, visit_method) {
+///     /// Field docs
+///     field: StoredType { new: ArgumentType, visit: ParameterType },
+/// }
 /// ```
-pub mod expr {
-    use crate::token::{Token, TokenType
+///
+/// and produces the struct variant, a `Visitor` method, a `TryVisitor`
+/// method, an arm in `accept` and `try_accept`, and a constructor.
+/// `ArgumentType` is what the constructor takes and is converted into
+/// `StoredType` with `From` (so an `Expr` argument is boxed), while
+/// `ParameterType` is what the visit methods receive.
+macro_rules! define_ast {
+    (
+        $(#[$enum_meta:meta])*
+        pub enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident($constructor:ident, $visit:ident) {
+                    $(
+                        $(#[doc = $field_doc:literal])*
+                        $field:ident: $field_ty:ty { new: $arg_ty:ty, visit: $param_ty:ty }
+                    ),* $(,)?
+                }
+            )*
+        }
+    ) => {
+        $(#[$enum_meta])*
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant {
+                    $(
+                        $(#[doc = $field_doc])*
+                        $field: $field_ty,
+                    )*
+                },
+            )*
+        }
+
+        /// Defines the Visitor trait for expression traversal
+        ///
+        /// Implement this trait to process different expression types.
+        /// Each visit method corresponds to a specific expression variant.
+        pub trait Visitor<T> {
+            $(
+                #[doc = concat!("Processes ", stringify!($variant), " expressions")]
+                fn $visit(&mut self, $($field: $param_ty),*) -> T;
+            )*
+        }
+
+        /// Defines a fallible Visitor for expression traversal
+        ///
+        /// Mirrors `Visitor`, but every method returns `Result<T, E>`, so an
+        /// implementation can use `?` on child results and stop at the first
+        /// error. Use `Visitor` for traversals that can't fail, such as printers.
+        pub trait TryVisitor<T, E> {
+            $(
+                #[doc = concat!("Processes ", stringify!($variant), " expressions")]
+                fn $visit(&mut self, $($field: $param_ty),*) -> Result<T, E>;
+            )*
+        }
+
+        impl $name {
+            /// Accepts a visitor to traverse the expression tree
+            ///
+            /// This method implements the Visitor pattern, dispatching to the
+            /// appropriate visitor method based on the expression type.
+            ///
+            /// # Arguments
+            /// * `visitor` - The visitor instance to process the expression
+            ///
+            /// # Returns
+            /// The result of the visitor operation
+            pub fn accept<T>(&self, visitor: &mut dyn Visitor<T>) -> T {
+                match self {
+                    $(
+                        $name::$variant { $($field),* } => visitor.$visit($($field),*),
+                    )*
+                }
+            }
+
+            /// Accepts a fallible visitor to traverse the expression tree
+            ///
+            /// Dispatches exactly like `accept`, but the visitor's methods return
+            /// `Result`, so errors propagate to the caller with `?`.
+            ///
+            /// # Arguments
+            /// * `visitor` - The visitor instance to process the expression
+            ///
+            /// # Returns
+            /// The result of the visitor operation, or the first error it reported
+            pub fn try_accept<T, E>(&self, visitor: &mut dyn TryVisitor<T, E>) -> Result<T, E> {
+                match self {
+                    $(
+                        $name::$variant { $($field),* } => visitor.$visit($($field),*),
+                    )*
+                }
+            }
+
+            $(
+                #[doc = concat!("Creates a new ", stringify!($variant), " expression")]
+                #[doc = ""]
+                #[doc = "# Arguments"]
+                $(
+                    #[doc = concat!("* `", stringify!($field), "` -", $($field_doc),*)]
+                )*
+                #[doc = ""]
+                #[doc = "# Returns"]
+                #[doc = concat!(stringify!($variant), " expression instance")]
+                pub fn $constructor($($field: $arg_ty),*) -> Self {
+                    $name::$variant { $($field: From::from($field)),* }
+                }
+            )*
+        }
     };
+}
 
+define_ast! {
     /// Represents any expression in the Lox language.
     ///
-    /// Expressions can be literals, unary operations, binary operations, or groupings.
+    /// Expressions can be literals, unary operations, binary operations, groupings,
+    /// conditionals, array and map literals, index accesses, or ranges.
     /// This enum implements the Visitor pattern through the `accept` method.
     #[derive(Debug, Clone, PartialEq)]
     pub enum Expr {
         /// Binary operation expression (e.g., 1 + 2)
-        Binary {
+        Binary(binary, visit_binary) {
             /// Left operand expression
-            left: Box<Expr>,
+            left: Box<Expr> { new: Expr, visit: &Expr },
 
             /// Operator token (e.g., Plus, Minus, Star, etc.)
-            operator: Token,
+            operator: Token { new: Token, visit: &Token },
 
             /// Right operand expression
-            right: Box<Expr>,
-        },
+            right: Box<Expr> { new: Expr, visit: &Expr },
+        }
 
         /// Grouping expression (e.g., (1 + 2))
-        Grouping {
+        Grouping(grouping, visit_grouping) {
             /// The expression inside the parentheses
-            expression: Box<Expr>,
-        },
+            expression: Box<Expr> { new: Expr, visit: &Expr },
+        }
 
         /// Literal value expression (e.g., 42, "hello", true, nil)
-        Literal {
+        Literal(literal, visit_literal) {
             /// The literal value
-            value: LiteralValue
-        },
+            value: LiteralValue { new: LiteralValue, visit: &LiteralValue },
+        }
 
         /// Unary operation expression (e.g., -42, !false)
-        Unary {
+        Unary(unary, visit_unary) {
             /// Operator token (e.g., Minus, Bang)
-            operator: Token,
-            
+            operator: Token { new: Token, visit: &Token },
+
             /// Right operand expression
-            right: Box<Expr>,
+            right: Box<Expr> { new: Expr, visit: &Expr },
+        }
+
+        /// Ternary conditional expression (e.g., a ? b : c)
+        Ternary(ternary, visit_ternary) {
+            /// Condition deciding which branch is evaluated
+            condition: Box<Expr> { new: Expr, visit: &Expr },
+
+            /// Expression evaluated when the condition is truthy
+            then_branch: Box<Expr> { new: Expr, visit: &Expr },
+
+            /// Expression evaluated when the condition is falsy
+            else_branch: Box<Expr> { new: Expr, visit: &Expr },
+        }
+
+        /// Array literal expression (e.g., [1, 2, 3])
+        Array(array, visit_array) {
+            /// Element expressions in source order
+            elements: Vec<Expr> { new: Vec<Expr>, visit: &[Expr] },
+        }
+
+        /// Index expression (e.g., arr[0])
+        Index(index, visit_index) {
+            /// Expression producing the indexed value
+            object: Box<Expr> { new: Expr, visit: &Expr },
+
+            /// Opening bracket token, used for error reporting
+            bracket: Token { new: Token, visit: &Token },
+
+            /// Expression producing the index
+            index: Box<Expr> { new: Expr, visit: &Expr },
+        }
+
+        /// Map literal expression (e.g., {"a": 1, "b": 2})
+        ///
+        /// Braces are shared with blocks, so the grammar position decides the
+        /// meaning: a `{` at the start of a statement always opens a block,
+        /// while a `{` where an expression is expected opens a map. An empty
+        /// `{}` is therefore an empty block as a statement and an empty map as
+        /// an operand (`var m = {};`). A map used as an expression statement
+        /// must be parenthesized: `({"a": 1});`.
+        Map(map, visit_map) {
+            /// Key and value expressions in source order
+            entries: Vec<(Expr, Expr)> { new: Vec<(Expr, Expr)>, visit: &[(Expr, Expr)] },
+        }
+
+        /// Numeric range expression (e.g., 0..10)
+        Range(range, visit_range) {
+            /// First number in the range
+            start: Box<Expr> { new: Expr, visit: &Expr },
+
+            /// Number the range stops before
+            end: Box<Expr> { new: Expr, visit: &Expr },
         }
     }
+}
 
-    /// Represents possible literal values in expressions
-    #[derive(Debug, Clone, PartialEq)]
-    pub enum LiteralValue {
-        /// Floating-point number (e.g., 123, 123.45)
-        Number(f64),
-        
-        /// String value (e.g., "hello")
-        String(String),
-
-        /// Boolean value (true or false)
-        Bool(bool),
-
-        /// Nil value
-        Nil,
+impl Expr {
+    /// Measures how deeply this expression nests.
+    ///
+    /// # Returns
+    /// Edges on the longest path from this node to a leaf; 0 for a literal
+    pub fn depth(&self) -> usize {
+        crate::metrics::measure(self).depth
     }
 
-    /// Defines the Visitor trait for expression traversal
+    /// Counts the nodes in this expression tree.
     ///
-    /// Implement this trait to process different expression types.
-    /// Each visit method corresponds to a specific expression variant.
-    pub trait Visitor<T> {
-        /// Processes a Binary expression
-        fn visit_binary(&mut self, left: &Expr, operator: &Token, right: &Expr) -> T;
-        /// Processes a Grouping expression
-        fn visit_grouping(&mut self, expression: &Expr) -> T;
+    /// # Returns
+    /// Number of nodes, this one included
+    pub fn node_count(&self) -> usize {
+        crate::metrics::measure(self).node_count
+    }
 
-        /// Processes a Literal expression 
-        fn visit_literal(&mut self, value: &LiteralValue) -> T;
+    /// Compares two trees while ignoring where their tokens came from.
+    ///
+    /// `==` compares tokens in full, so the same expression written on
+    /// different lines or columns isn't equal. This compares the shape of
+    /// the trees, their literal values, and each token's type and lexeme
+    /// only.
+    ///
+    /// # Arguments
+    /// * `other` - Tree to compare against
+    ///
+    /// # Returns
+    /// True if the trees have the same structure, operators and values
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::parser::Parser;
+    /// use jaloxc::scanner::Scanner;
+    ///
+    /// let parse = |source: &str| Parser::new(Scanner::new(source).scan_tokens().clone()).parse().unwrap();
+    /// let (a, b) = (parse("1 + 2 * [3, 4][0]"), parse("\n    1 +\n        2*[3,4] [ 0 ]"));
+    ///
+    /// assert_ne!(a, b);
+    /// assert!(a.structural_eq(&b));
+    /// assert!(!a.structural_eq(&parse("1 - 2 * [3, 4][0]")));
+    /// ```
+    pub fn structural_eq(&self, other: &Expr) -> bool {
+        match (self, other) {
+            (
+                Expr::Binary { left, operator, right },
+                Expr::Binary { left: other_left, operator: other_operator, right: other_right },
+            ) => {
+                same_token(operator, other_operator)
+                    && left.structural_eq(other_left)
+                    && right.structural_eq(other_right)
+            }
+            (Expr::Grouping { expression }, Expr::Grouping { expression: other_expression }) => {
+                expression.structural_eq(other_expression)
+            }
+            (Expr::Literal { value }, Expr::Literal { value: other_value }) => value == other_value,
+            (
+                Expr::Unary { operator, right },
+                Expr::Unary { operator: other_operator, right: other_right },
+            ) => same_token(operator, other_operator) && right.structural_eq(other_right),
+            (
+                Expr::Ternary { condition, then_branch, else_branch },
+                Expr::Ternary {
+                    condition: other_condition,
+                    then_branch: other_then,
+                    else_branch: other_else,
+                },
+            ) => {
+                condition.structural_eq(other_condition)
+                    && then_branch.structural_eq(other_then)
+                    && else_branch.structural_eq(other_else)
+            }
+            (Expr::Array { elements }, Expr::Array { elements: other_elements }) => {
+                elements.len() == other_elements.len()
+                    && elements.iter().zip(other_elements).all(|(a, b)| a.structural_eq(b))
+            }
+            (
+                Expr::Index { object, bracket, index },
+                Expr::Index { object: other_object, bracket: other_bracket, index: other_index },
+            ) => {
+                same_token(bracket, other_bracket)
+                    && object.structural_eq(other_object)
+                    && index.structural_eq(other_index)
+            }
+            (Expr::Map { entries }, Expr::Map { entries: other_entries }) => {
+                entries.len() == other_entries.len()
+                    && entries.iter().zip(other_entries).all(|((key, value), (other_key, other_value))| {
+                        key.structural_eq(other_key) && value.structural_eq(other_value)
+                    })
+            }
+            (Expr::Range { start, end }, Expr::Range { start: other_start, end: other_end }) => {
+                start.structural_eq(other_start) && end.structural_eq(other_end)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Compares two tokens by type and lexeme, ignoring their position.
+///
+/// # Arguments
+/// * `a` - First token
+/// * `b` - Second token
+///
+/// # Returns
+/// True if the tokens have the same type and text
+fn same_token(a: &Token, b: &Token) -> bool {
+    a.token_type == b.token_type && a.lexeme == b.lexeme
+}
+
+/// Represents possible literal values in expressions
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiteralValue {
+    /// Floating-point number (e.g., 123, 123.45)
+    Number(f64),
+
+    /// Integer too long for a `Number` to hold exactly (e.g.,
+    /// 12345678901234567890)
+    ///
+    /// Integer arithmetic on big integers stays exact; mixing one with a
+    /// `Number`, dividing, or overflowing an i128 gives a `Number`.
+    BigInt(i128),
+    
+    /// String value (e.g., "hello")
+    String(String),
+
+    /// Boolean value (true or false)
+    Bool(bool),
+
+    /// Nil value
+    Nil,
 
-        /// Processes a Unary expression
-        fn visit_unary(&mut self, operator: &Token, right: &Expr) -> T;
+    /// Array value, shared by reference so mutations are visible through
+    /// every alias
+    Array(Rc<RefCell<Vec<LiteralValue>>>),
+
+    /// Map value, shared by reference like arrays
+    ///
+    /// Keys are strings or numbers. Entries keep their insertion order so
+    /// maps print and iterate deterministically.
+    Map(Rc<RefCell<Vec<(LiteralValue, LiteralValue)>>>),
+
+    /// Half-open numeric range (e.g., 0..10), from the first number up to
+    /// but not including the second
+    ///
+    /// Unlike arrays, ranges are plain values: two ranges with the same
+    /// bounds are equal.
+    Range(f64, f64),
+}
+
+impl LiteralValue {
+    /// Compares two values with Lox's `==` semantics.
+    ///
+    /// Values of different types are never equal. Numbers follow IEEE 754,
+    /// so `NaN` is not equal to anything, itself included. Big integers and
+    /// numbers are both numbers, and are equal when they hold exactly the
+    /// same value. Arrays are equal when their elements are pairwise equal,
+    /// maps when they have the same keys mapping to equal values in any
+    /// order, and ranges when their bounds are. An array or map is always
+    /// equal to itself.
+    ///
+    /// Arrays and maps can contain themselves. A pair of containers met
+    /// again while they are still being compared is taken to be equal, so
+    /// comparing cyclic values terminates.
+    ///
+    /// # Arguments
+    /// * `other` - Value to compare against
+    ///
+    /// # Returns
+    /// True if the values are equal, false otherwise
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use jaloxc::expr::LiteralValue;
+    ///
+    /// let nan = LiteralValue::Number(f64::NAN);
+    /// assert!(!nan.is_equal(&nan));
+    /// assert!(LiteralValue::Nil.is_equal(&LiteralValue::Nil));
+    /// assert!(!LiteralValue::Nil.is_equal(&LiteralValue::Bool(false)));
+    ///
+    /// let array = |values: Vec<LiteralValue>| LiteralValue::Array(Rc::new(RefCell::new(values)));
+    /// let nested = array(vec![LiteralValue::Number(1.0), array(vec![LiteralValue::Nil])]);
+    /// let copy = array(vec![LiteralValue::Number(1.0), array(vec![LiteralValue::Nil])]);
+    /// assert!(nested.is_equal(&copy));
+    /// assert!(!nested.is_equal(&array(vec![LiteralValue::Number(1.0)])));
+    ///
+    /// // Two arrays that each contain themselves
+    /// let (a, b) = (array(vec![]), array(vec![]));
+    /// for value in [&a, &b] {
+    ///     if let LiteralValue::Array(elements) = value {
+    ///         elements.borrow_mut().push(value.clone());
+    ///     }
+    /// }
+    /// assert!(a.is_equal(&b));
+    /// ```
+    pub fn is_equal(&self, other: &LiteralValue) -> bool {
+        self.is_equal_within(other, &mut Vec::new())
     }
 
-    impl Expr {
-        /// Accepts a visitor to traverse the expression tree
-        ///
-        /// This method implements the Visitor pattern, dispatching to the
-        /// appropriate visitor method based on the expression type.
-        ///
-        /// # Arguments
-        /// * `visitor` - The visitor instance to process the expression
-        ///
-        /// # Returns
-        /// The result of the visitor operation 
-        pub fn accept<T>(&self, visitor: &mut dyn Visitor<T>) -> T {
-            match self {
-                Expr::Binary { left, operator, right } => {
-                    visitor.visit_binary(left, operator, right)
-                }
-                Expr::Grouping { expression } => {
-                    visitor.visit_grouping(expression)
-                }
-                Expr::Literal { value } => {
-                    visitor.visit_literal(value)
-                }
-                Expr::Unary { operator, right } => {
-                    visitor.visit_unary(operator, right)
-                }
+    /// Compares two values, skipping container pairs already being compared.
+    ///
+    /// # Arguments
+    /// * `other` - Value to compare against
+    /// * `active` - Addresses of the container pairs currently being compared
+    ///
+    /// # Returns
+    /// True if the values are equal, false otherwise
+    fn is_equal_within(&self, other: &LiteralValue, active: &mut Vec<(usize, usize)>) -> bool {
+        match (self, other) {
+            (LiteralValue::Number(a), LiteralValue::Number(b)) => a == b,
+            (LiteralValue::BigInt(a), LiteralValue::BigInt(b)) => a == b,
+            (LiteralValue::BigInt(a), LiteralValue::Number(b))
+            | (LiteralValue::Number(b), LiteralValue::BigInt(a)) => {
+                // A float equals an integer only if it is that exact integer
+                b.fract() == 0.0 && *a as f64 == *b && *b as i128 == *a
+            }
+            (LiteralValue::String(a), LiteralValue::String(b)) => a == b,
+            (LiteralValue::Bool(a), LiteralValue::Bool(b)) => a == b,
+            (LiteralValue::Nil, LiteralValue::Nil) => true,
+            (LiteralValue::Range(a, b), LiteralValue::Range(c, d)) => a == c && b == d,
+            (LiteralValue::Array(a), LiteralValue::Array(b)) => {
+                Self::compare_containers(a, b, active, |a, b, active| {
+                    a.len() == b.len()
+                        && a.iter().zip(b.iter()).all(|(x, y)| x.is_equal_within(y, active))
+                })
             }
+            (LiteralValue::Map(a), LiteralValue::Map(b)) => {
+                Self::compare_containers(a, b, active, |a, b, active| {
+                    a.len() == b.len()
+                        && a.iter().all(|(key, value)| {
+                            b.iter().any(|(other_key, other_value)| {
+                                key.is_equal_within(other_key, active)
+                                    && value.is_equal_within(other_value, active)
+                            })
+                        })
+                })
+            }
+            _ => false,
         }
+    }
 
-        /// Creates a new Binary expression
-        ///
-        /// # Arguments
-        /// * `left` - Left operand expression
-        /// * `operator` - Operator token
-        /// * `right` - Right operand expression
-        ///
-        /// # Returns
-        /// Binary expression instance
-        pub fn binary(left: Expr, operator: Token, right: Expr) -> Self {
-            Expr::Binary { 
-                left: Box::new(left),
-                operator,
-                right: Box::new(right),
-            }
+    /// Compares the contents of two shared containers.
+    ///
+    /// # Arguments
+    /// * `a` - First container
+    /// * `b` - Second container
+    /// * `active` - Addresses of the container pairs currently being compared
+    /// * `contents_equal` - Compares the borrowed contents
+    ///
+    /// # Returns
+    /// True if the containers are the same object, already being compared,
+    /// or have equal contents
+    fn compare_containers<T>(
+        a: &Rc<RefCell<T>>,
+        b: &Rc<RefCell<T>>,
+        active: &mut Vec<(usize, usize)>,
+        contents_equal: impl FnOnce(&T, &T, &mut Vec<(usize, usize)>) -> bool,
+    ) -> bool {
+        let pair = (Rc::as_ptr(a) as usize, Rc::as_ptr(b) as usize);
+        if Rc::ptr_eq(a, b) || active.contains(&pair) {
+            return true;
         }
 
-        /// Creates a new Grouping expression
-        ///
-        /// # Arguments
-        /// * `expression` - Expression to group
-        ///
-        /// # Returns
-        /// Grouping expression instance
-        pub fn grouping(expression: Expr) -> Self {
-            Expr::Grouping { 
-                expression: Box::new(expression),
+        active.push(pair);
+        let equal = contents_equal(&a.borrow(), &b.borrow(), active);
+        active.pop();
+        equal
+    }
+
+    /// Copies a value, giving it arrays and maps of its own.
+    ///
+    /// Arrays and maps are shared by reference, so a plain `clone` still
+    /// aliases them. A deep copy rebuilds every container reachable from
+    /// the value in fresh cells; scalars are copied as they are. A container
+    /// reached more than once, including through a cycle, is copied once
+    /// and the copy shared the same way, so cyclic values are copied
+    /// without recursing forever.
+    ///
+    /// # Returns
+    /// A value equal to this one that shares no containers with it
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use jaloxc::expr::LiteralValue;
+    ///
+    /// let elements = Rc::new(RefCell::new(vec![LiteralValue::Number(1.0)]));
+    /// let original = LiteralValue::Array(elements.clone());
+    /// let copy = original.deep_copy();
+    ///
+    /// if let LiteralValue::Array(copied) = &copy {
+    ///     copied.borrow_mut().push(LiteralValue::Number(2.0));
+    /// }
+    /// assert_eq!(elements.borrow().len(), 1);
+    /// assert_eq!(copy.to_string(), "[1, 2]");
+    ///
+    /// // An array containing itself copies to a new array containing itself
+    /// elements.borrow_mut().push(original.clone());
+    /// let LiteralValue::Array(copied) = original.deep_copy() else { unreachable!() };
+    /// let LiteralValue::Array(inner) = &copied.borrow()[1] else { unreachable!() };
+    /// assert!(Rc::ptr_eq(inner, &copied) && !Rc::ptr_eq(inner, &elements));
+    /// ```
+    pub fn deep_copy(&self) -> LiteralValue {
+        self.deep_copy_within(&mut HashMap::new())
+    }
+
+    /// Deep-copies a value, reusing copies of containers already copied.
+    ///
+    /// # Arguments
+    /// * `copies` - Copies made so far, keyed by the original's address
+    ///
+    /// # Returns
+    /// The copied value
+    fn deep_copy_within(&self, copies: &mut HashMap<usize, LiteralValue>) -> LiteralValue {
+        match self {
+            LiteralValue::Array(elements) => {
+                let key = Rc::as_ptr(elements) as usize;
+                if let Some(copy) = copies.get(&key) {
+                    return copy.clone();
+                }
+
+                // Registered before the elements are copied so a cycle back
+                // to this array finds it
+                let copy = Rc::new(RefCell::new(Vec::new()));
+                copies.insert(key, LiteralValue::Array(copy.clone()));
+                let copied = elements.borrow().iter().map(|element| element.deep_copy_within(copies)).collect();
+                *copy.borrow_mut() = copied;
+                LiteralValue::Array(copy)
             }
-        } 
+            LiteralValue::Map(entries) => {
+                let key = Rc::as_ptr(entries) as usize;
+                if let Some(copy) = copies.get(&key) {
+                    return copy.clone();
+                }
 
-        /// Creates a new Literal expression
-        ///
-        /// # Arguments
-        /// * `value` - Literal value
-        ///
-        /// # Returns
-        /// Literal expression instance
-        pub fn literal(value: LiteralValue) -> Self {
-            Expr::Literal { value }
+                let copy = Rc::new(RefCell::new(Vec::new()));
+                copies.insert(key, LiteralValue::Map(copy.clone()));
+                let copied = entries
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| (key.deep_copy_within(copies), value.deep_copy_within(copies)))
+                    .collect();
+                *copy.borrow_mut() = copied;
+                LiteralValue::Map(copy)
+            }
+            _ => self.clone(),
         }
+    }
+}
 
-        /// Creates a new Unary expression
-        ///
-        /// # Arguments
-        /// * `operator` - Operator token
-        /// * `right` - Right operand expression
-        ///
-        /// # Returns
-        /// Unary expression instance
-        pub fn unary(operator: Token, right: Expr) -> Self {
-            Expr::Unary { 
-                operator,
-                right: Box::new(right),
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Expr::Binary { left, operator, right } => {
+                write!(f, "({} {} {})", operator.lexeme, left, right)
+            }
+            Expr::Grouping { expression } => {
+                write!(f, "(group {})", expression)
+            }
+            Expr::Literal { value } => fmt_literal(value, f),
+            Expr::Unary { operator, right } => {
+                write!(f, "{} {}", operator.lexeme, right)
+            }
+            Expr::Ternary { condition, then_branch, else_branch } => {
+                write!(f, "(?: {} {} {})", condition, then_branch, else_branch)
+            }
+            Expr::Array { elements } => {
+                write!(f, "(array")?;
+                for element in elements {
+                    write!(f, " {}", element)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Index { object, index, .. } => {
+                write!(f, "(index {} {})", object, index)
+            }
+            Expr::Map { entries } => {
+                write!(f, "(map")?;
+                for (key, value) in entries {
+                    write!(f, " {} {}", key, value)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Range { start, end } => {
+                write!(f, "(.. {} {})", start, end)
             }
         }
     }
+}
 
-    impl std::fmt::Display for Expr {
-        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-            match self {
-                Expr::Binary { left, operator, right } => {
-                    write!(f, "({} {} {})", operator.lexeme, left, right)
-                }
-                Expr::Grouping { expression } => {
-                    write!(f, "(group {})", expression)
+/// Formats a value the way Lox's `print` shows it.
+///
+/// Numbers print as the shortest decimal that reads back as the same
+/// value, so `0.1 + 0.2` shows every digit that distinguishes it from
+/// `0.3`, as the reference implementation does. Integral numbers have no
+/// trailing `.0` (`5.0` prints as `5`), strings are printed without
+/// quotes, and `Nil` prints as `nil`. Special numbers print as `NaN`,
+/// `Infinity` and `-Infinity`, and ranges as `start..end`.
+///
+/// An array or map that contains itself prints as `[...]` or `{...}` where
+/// it recurs.
+///
+/// # Examples
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use jaloxc::expr::LiteralValue;
+///
+/// assert_eq!(LiteralValue::Number(5.0).to_string(), "5");
+/// assert_eq!(LiteralValue::Number(0.1 + 0.2).to_string(), "0.30000000000000004");
+/// assert_eq!(LiteralValue::Number(2.50).to_string(), "2.5");
+/// assert_eq!(LiteralValue::BigInt(12345678901234567890).to_string(), "12345678901234567890");
+/// assert_eq!(LiteralValue::Number(f64::NAN).to_string(), "NaN");
+/// assert_eq!(LiteralValue::Number(f64::INFINITY).to_string(), "Infinity");
+/// assert_eq!(LiteralValue::Number(f64::NEG_INFINITY).to_string(), "-Infinity");
+/// assert_eq!(LiteralValue::Range(0.0, 10.0).to_string(), "0..10");
+///
+/// // An array that contains itself shows `[...]` where it recurs
+/// let elements = Rc::new(RefCell::new(vec![LiteralValue::Number(1.0)]));
+/// let array = LiteralValue::Array(elements.clone());
+/// elements.borrow_mut().push(array.clone());
+/// assert_eq!(array.to_string(), "[1, [...]]");
+///
+/// // A container shared without a cycle prints in full each time
+/// let shared = LiteralValue::Array(Rc::new(RefCell::new(vec![LiteralValue::Nil])));
+/// let pair = LiteralValue::Array(Rc::new(RefCell::new(vec![shared.clone(), shared])));
+/// assert_eq!(pair.to_string(), "[[nil], [nil]]");
+/// ```
+impl std::fmt::Display for LiteralValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt_value(self, f, false, &mut Vec::new())
+    }
+}
+
+/// Formats a literal value as it appears in a printed expression tree.
+///
+/// The same as `Display`, except that strings are quoted.
+fn fmt_literal(value: &LiteralValue, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fmt_value(value, f, true, &mut Vec::new())
+}
+
+/// Formats a value, printing arrays and maps element by element.
+///
+/// An array or map met again while its own elements are being printed
+/// is shown as `[...]` or `{...}`, so a value that contains itself
+/// prints without recursing forever.
+///
+/// # Arguments
+/// * `value` - Value to format
+/// * `f` - Formatter to write to
+/// * `quote_strings` - Whether strings, nested ones included, are quoted
+/// * `active` - Addresses of the containers currently being printed
+fn fmt_value(
+    value: &LiteralValue,
+    f: &mut std::fmt::Formatter,
+    quote_strings: bool,
+    active: &mut Vec<usize>,
+) -> std::fmt::Result {
+    match value {
+        LiteralValue::Number(n) => fmt_number(*n, f),
+        LiteralValue::BigInt(n) => write!(f, "{}", n),
+        LiteralValue::String(s) if quote_strings => write!(f, "\"{}\"", s),
+        LiteralValue::String(s) => write!(f, "{}", s),
+        LiteralValue::Bool(b) => write!(f, "{}", b),
+        LiteralValue::Nil => write!(f, "nil"),
+        LiteralValue::Array(elements) => {
+            let address = Rc::as_ptr(elements) as usize;
+            if active.contains(&address) {
+                return write!(f, "[...]");
+            }
+
+            active.push(address);
+            write!(f, "[")?;
+            for (i, element) in elements.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
                 }
-                Expr::Literal { value } => match value {
-                    LiteralValue::Number(n) => write!(f, "{}", n),
-                    LiteralValue::String(s) => write!(f, "\"{}\"", s),
-                    LiteralValue::Bool(b)=> write!(f, "{}", b),
-                    LiteralValue::Nil => write!(f, "nil"),
-                },
-                Expr::Unary { operator, right } => {
-                    write!(f, "{} {}", operator.lexeme, right)
+                fmt_value(element, f, quote_strings, active)?;
+            }
+            active.pop();
+            write!(f, "]")
+        }
+        LiteralValue::Map(entries) => {
+            let address = Rc::as_ptr(entries) as usize;
+            if active.contains(&address) {
+                return write!(f, "{{...}}");
+            }
+
+            active.push(address);
+            write!(f, "{{")?;
+            for (i, (key, value)) in entries.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
                 }
+                fmt_value(key, f, quote_strings, active)?;
+                write!(f, ": ")?;
+                fmt_value(value, f, quote_strings, active)?;
             }
+            active.pop();
+            write!(f, "}}")
+        }
+        LiteralValue::Range(start, end) => {
+            fmt_number(*start, f)?;
+            write!(f, "..")?;
+            fmt_number(*end, f)
         }
     }
 }
 
+/// Formats a number the way Lox prints it.
+///
+/// Rust's shortest round-trip formatting already omits `.0`; only the
+/// special values need spelling out.
+fn fmt_number(n: f64, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    if n.is_nan() {
+        write!(f, "NaN")
+    } else if n.is_infinite() {
+        write!(f, "{}Infinity", if n < 0.0 { "-" } else { "" })
+    } else {
+        write!(f, "{}", n)
+    }
+}
@@ -0,0 +1,297 @@
+/// Constant folding for Lox expression trees.
+///
+/// Rewrites subexpressions whose operands are all literals into a single
+/// literal before the tree is evaluated, so `2 + 3 * 4` becomes `14`.
+/// Anything that would fail at runtime, such as adding a number to a
+/// string, is left as written so the error is still reported when the
+/// program runs. Division by zero isn't an error in Lox: `1 / 0` folds to
+/// `Infinity`.
+use crate::expr::{Expr, LiteralValue, Visitor};
+use crate::token::{Token, TokenType};
+
+/// Visitor that rebuilds an expression tree with constant subtrees folded.
+///
+/// Children are folded first, so a node folds whenever all of its operands
+/// folded down to literals.
+#[derive(Default)]
+pub struct ConstantFolder;
+
+impl ConstantFolder {
+    /// Creates a new constant folder.
+    ///
+    /// # Returns
+    /// New ConstantFolder instance
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Folds every constant subexpression of the given tree.
+    ///
+    /// # Arguments
+    /// * `expr` - Root of the expression tree to fold
+    ///
+    /// # Returns
+    /// An equivalent tree with constant subtrees replaced by literals
+    pub fn fold(&mut self, expr: &Expr) -> Expr {
+        expr.accept(self)
+    }
+}
+
+impl Visitor<Expr> for ConstantFolder {
+    fn visit_binary(&mut self, left: &Expr, operator: &Token, right: &Expr) -> Expr {
+        let left = self.fold(left);
+        let right = self.fold(right);
+
+        if let (Some(a), Some(b)) = (literal_of(&left), literal_of(&right))
+            && let Some(value) = fold_binary(a, operator.token_type, b)
+        {
+            return Expr::literal(value);
+        }
+
+        Expr::binary(left, operator.clone(), right)
+    }
+
+    fn visit_grouping(&mut self, expression: &Expr) -> Expr {
+        let expression = self.fold(expression);
+        match expression {
+            Expr::Literal { .. } => expression,
+            _ => Expr::grouping(expression),
+        }
+    }
+
+    fn visit_literal(&mut self, value: &LiteralValue) -> Expr {
+        Expr::literal(value.clone())
+    }
+
+    fn visit_unary(&mut self, operator: &Token, right: &Expr) -> Expr {
+        let right = self.fold(right);
+
+        let folded = match (operator.token_type, literal_of(&right)) {
+            (TokenType::Minus, Some(LiteralValue::Number(n))) => Some(LiteralValue::Number(-n)),
+            (TokenType::Minus, Some(LiteralValue::BigInt(n))) => Some(
+                n.checked_neg()
+                    .map_or(LiteralValue::Number(-(*n as f64)), LiteralValue::BigInt),
+            ),
+            (TokenType::Bang, Some(value)) => Some(LiteralValue::Bool(!is_truthy(value))),
+            _ => None,
+        };
+
+        match folded {
+            Some(value) => Expr::literal(value),
+            None => Expr::unary(operator.clone(), right),
+        }
+    }
+
+    fn visit_ternary(&mut self, condition: &Expr, then_branch: &Expr, else_branch: &Expr) -> Expr {
+        let condition = self.fold(condition);
+
+        // Only the branch that would run is kept, and only it is folded
+        match literal_of(&condition) {
+            Some(value) if is_truthy(value) => self.fold(then_branch),
+            Some(_) => self.fold(else_branch),
+            None => Expr::ternary(condition, self.fold(then_branch), self.fold(else_branch)),
+        }
+    }
+
+    fn visit_array(&mut self, elements: &[Expr]) -> Expr {
+        Expr::array(elements.iter().map(|element| self.fold(element)).collect())
+    }
+
+    fn visit_index(&mut self, object: &Expr, bracket: &Token, index: &Expr) -> Expr {
+        Expr::index(self.fold(object), bracket.clone(), self.fold(index))
+    }
+
+    fn visit_map(&mut self, entries: &[(Expr, Expr)]) -> Expr {
+        Expr::map(
+            entries
+                .iter()
+                .map(|(key, value)| (self.fold(key), self.fold(value)))
+                .collect(),
+        )
+    }
+
+    fn visit_range(&mut self, start: &Expr, end: &Expr) -> Expr {
+        let start = self.fold(start);
+        let end = self.fold(end);
+
+        match (literal_of(&start), literal_of(&end)) {
+            (Some(LiteralValue::Number(a)), Some(LiteralValue::Number(b))) => {
+                Expr::literal(LiteralValue::Range(*a, *b))
+            }
+            _ => Expr::range(start, end),
+        }
+    }
+}
+
+/// Folds the constant subexpressions of an expression tree.
+///
+/// # Arguments
+/// * `expr` - Expression tree to fold
+///
+/// # Returns
+/// An equivalent tree with constant subtrees replaced by literals
+///
+/// # Examples
+/// ```
+/// use jaloxc::expr::{Expr, LiteralValue};
+/// use jaloxc::fold::fold_constants;
+/// use jaloxc::parser::Parser;
+/// use jaloxc::scanner::Scanner;
+///
+/// let tokens = Scanner::new("2 + 3 * 4").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+/// assert_eq!(fold_constants(expr), Expr::literal(LiteralValue::Number(14.0)));
+///
+/// // Mixed operand types are left for the runtime to report
+/// let tokens = Scanner::new("1 + \"a\"").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+/// assert_eq!(fold_constants(expr.clone()), expr);
+///
+/// // Division by zero follows IEEE 754
+/// let tokens = Scanner::new("1 / 0").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+/// assert_eq!(fold_constants(expr).to_string(), "Infinity");
+///
+/// // A remainder by zero is left for the runtime, for small and long
+/// // integers alike
+/// for source in ["1 % 0", "99999999999999999999 % 0"] {
+///     let tokens = Scanner::new(source).scan_tokens().clone();
+///     let expr = Parser::new(tokens).parse().unwrap();
+///     assert_eq!(fold_constants(expr.clone()), expr);
+/// }
+///
+/// // Strings compare lexicographically; a prefix sorts first
+/// let tokens = Scanner::new("\"ab\" < \"abc\"").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+/// assert_eq!(fold_constants(expr), Expr::literal(LiteralValue::Bool(true)));
+///
+/// // Long integers keep every digit until mixed with a float
+/// let tokens = Scanner::new("99999999999999999999 + 1").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+/// assert_eq!(fold_constants(expr).to_string(), "100000000000000000000");
+///
+/// // Ranges with constant bounds become range values
+/// let tokens = Scanner::new("0..2 + 3").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+/// assert_eq!(fold_constants(expr), Expr::literal(LiteralValue::Range(0.0, 5.0)));
+/// ```
+pub fn fold_constants(expr: Expr) -> Expr {
+    ConstantFolder::new().fold(&expr)
+}
+
+/// Returns the value of an expression if it is a literal.
+///
+/// # Arguments
+/// * `expr` - Expression to inspect
+///
+/// # Returns
+/// The literal value, or None for any other expression
+fn literal_of(expr: &Expr) -> Option<&LiteralValue> {
+    match expr {
+        Expr::Literal { value } => Some(value),
+        _ => None,
+    }
+}
+
+/// Evaluates a binary operator on two constant operands.
+///
+/// Only operations that are certain to succeed at runtime are folded.
+///
+/// # Arguments
+/// * `left` - Left operand value
+/// * `operator` - Operator token type
+/// * `right` - Right operand value
+///
+/// # Returns
+/// The result, or None if the operation must be left to the runtime
+fn fold_binary(left: &LiteralValue, operator: TokenType, right: &LiteralValue) -> Option<LiteralValue> {
+    use LiteralValue::{BigInt, Bool, Number};
+
+    let value = match (left, operator, right) {
+        // The comma operator discards a literal left operand, which has no
+        // side effects
+        (_, TokenType::Comma, _) => right.clone(),
+
+        (_, TokenType::EqualEqual, _) => Bool(left.is_equal(right)),
+        (_, TokenType::BangEqual, _) => Bool(!left.is_equal(right)),
+
+        (BigInt(a), _, BigInt(b)) => return fold_big_int(*a, operator, *b),
+        (BigInt(a), _, Number(_)) => return fold_binary(&Number(*a as f64), operator, right),
+        (Number(_), _, BigInt(b)) => return fold_binary(left, operator, &Number(*b as f64)),
+
+        (Number(a), TokenType::Plus, Number(b)) => Number(a + b),
+        (Number(a), TokenType::Minus, Number(b)) => Number(a - b),
+        (Number(a), TokenType::Star, Number(b)) => Number(a * b),
+        (Number(a), TokenType::StarStar, Number(b)) => Number(a.powf(*b)),
+        (Number(a), TokenType::Slash, Number(b)) => Number(a / b),
+        (Number(a), TokenType::Percent, Number(b)) if *b != 0.0 => Number(a % b),
+        (Number(a), TokenType::Greater, Number(b)) => Bool(a > b),
+        (Number(a), TokenType::GreaterEqual, Number(b)) => Bool(a >= b),
+        (Number(a), TokenType::Less, Number(b)) => Bool(a < b),
+        (Number(a), TokenType::LessEqual, Number(b)) => Bool(a <= b),
+
+        (LiteralValue::String(a), TokenType::Plus, LiteralValue::String(b)) => {
+            LiteralValue::String(format!("{}{}", a, b))
+        }
+
+        // Strings order lexicographically, by code point
+        (LiteralValue::String(a), TokenType::Greater, LiteralValue::String(b)) => Bool(a > b),
+        (LiteralValue::String(a), TokenType::GreaterEqual, LiteralValue::String(b)) => Bool(a >= b),
+        (LiteralValue::String(a), TokenType::Less, LiteralValue::String(b)) => Bool(a < b),
+        (LiteralValue::String(a), TokenType::LessEqual, LiteralValue::String(b)) => Bool(a <= b),
+
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Evaluates a binary operator on two big integers.
+///
+/// Comparisons are exact. `+`, `-`, `*`, `%` and `**` with a non-negative
+/// exponent stay exact while the result fits in an i128; anything else,
+/// including `/` and overflow, is computed on numbers instead. `%` by zero
+/// is never folded.
+///
+/// # Arguments
+/// * `a` - Left operand
+/// * `operator` - Operator token type
+/// * `b` - Right operand
+///
+/// # Returns
+/// The result, or None if the operation must be left to the runtime
+fn fold_big_int(a: i128, operator: TokenType, b: i128) -> Option<LiteralValue> {
+    let exact = match operator {
+        TokenType::Percent if b == 0 => return None,
+        TokenType::Greater => return Some(LiteralValue::Bool(a > b)),
+        TokenType::GreaterEqual => return Some(LiteralValue::Bool(a >= b)),
+        TokenType::Less => return Some(LiteralValue::Bool(a < b)),
+        TokenType::LessEqual => return Some(LiteralValue::Bool(a <= b)),
+        TokenType::Plus => a.checked_add(b),
+        TokenType::Minus => a.checked_sub(b),
+        TokenType::Star => a.checked_mul(b),
+        TokenType::Percent => a.checked_rem(b),
+        TokenType::StarStar => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+        _ => None,
+    };
+
+    match exact {
+        Some(value) => Some(LiteralValue::BigInt(value)),
+        None => fold_binary(
+            &LiteralValue::Number(a as f64),
+            operator,
+            &LiteralValue::Number(b as f64),
+        ),
+    }
+}
+
+/// Determines whether a value counts as true in a condition.
+///
+/// # Arguments
+/// * `value` - Value to test
+///
+/// # Returns
+/// false for `nil` and `false`, true for everything else
+fn is_truthy(value: &LiteralValue) -> bool {
+    !matches!(value, LiteralValue::Nil | LiteralValue::Bool(false))
+}
@@ -0,0 +1,271 @@
+/// Canonical source formatting for Lox expressions.
+///
+/// Walks an `Expr` with a visitor and prints it back as Lox source in a
+/// normalized layout: single spaces around binary operators and `?`/`:`,
+/// a space after every comma and map colon, and no padding inside
+/// brackets, braces or parentheses. Parentheses come only from `Grouping`
+/// nodes, so a parsed tree is reprinted with exactly the grouping it was
+/// written with. Formatting already formatted source changes nothing.
+///
+/// Interpolated strings are desugared by the scanner, so they come back
+/// out as the equivalent concatenation.
+use std::rc::Rc;
+use crate::expr::{Expr, LiteralValue, Visitor};
+use crate::token::{Token, TokenType};
+
+/// Visitor that renders an expression tree as canonical Lox source.
+#[derive(Default)]
+pub struct SourceFormatter;
+
+impl SourceFormatter {
+    /// Creates a new formatter.
+    ///
+    /// # Returns
+    /// New SourceFormatter instance
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Formats an expression as source text.
+    ///
+    /// # Arguments
+    /// * `expr` - Root of the expression tree to format
+    ///
+    /// # Returns
+    /// Formatted source, without a trailing newline
+    pub fn format(&mut self, expr: &Expr) -> String {
+        expr.accept(self)
+    }
+
+    /// Formats a comma-separated list of expressions.
+    ///
+    /// # Arguments
+    /// * `exprs` - Expressions to format, in order
+    ///
+    /// # Returns
+    /// The formatted expressions joined with `, `
+    fn list(&mut self, exprs: &[Expr]) -> String {
+        let parts: Vec<String> = exprs.iter().map(|expr| self.format(expr)).collect();
+        parts.join(", ")
+    }
+}
+
+impl Visitor<String> for SourceFormatter {
+    fn visit_binary(&mut self, left: &Expr, operator: &Token, right: &Expr) -> String {
+        let left = self.format(left);
+        let right = self.format(right);
+        match operator.token_type {
+            TokenType::Comma => format!("{}, {}", left, right),
+            _ => format!("{} {} {}", left, operator.lexeme, right),
+        }
+    }
+
+    fn visit_grouping(&mut self, expression: &Expr) -> String {
+        format!("({})", self.format(expression))
+    }
+
+    fn visit_literal(&mut self, value: &LiteralValue) -> String {
+        literal_source(value)
+    }
+
+    fn visit_unary(&mut self, operator: &Token, right: &Expr) -> String {
+        let right = self.format(right);
+
+        // `- -x` must not be glued into the `--` token
+        if operator.token_type == TokenType::Minus && right.starts_with('-') {
+            format!("{} {}", operator.lexeme, right)
+        } else {
+            format!("{}{}", operator.lexeme, right)
+        }
+    }
+
+    fn visit_ternary(&mut self, condition: &Expr, then_branch: &Expr, else_branch: &Expr) -> String {
+        format!(
+            "{} ? {} : {}",
+            self.format(condition),
+            self.format(then_branch),
+            self.format(else_branch)
+        )
+    }
+
+    fn visit_array(&mut self, elements: &[Expr]) -> String {
+        format!("[{}]", self.list(elements))
+    }
+
+    fn visit_index(&mut self, object: &Expr, _bracket: &Token, index: &Expr) -> String {
+        format!("{}[{}]", self.format(object), self.format(index))
+    }
+
+    fn visit_map(&mut self, entries: &[(Expr, Expr)]) -> String {
+        let parts: Vec<String> = entries
+            .iter()
+            .map(|(key, value)| format!("{}: {}", self.format(key), self.format(value)))
+            .collect();
+        format!("{{{}}}", parts.join(", "))
+    }
+
+    fn visit_range(&mut self, start: &Expr, end: &Expr) -> String {
+        format!("{}..{}", self.format(start), self.format(end))
+    }
+}
+
+/// Formats an expression as canonical Lox source.
+///
+/// # Arguments
+/// * `expr` - Expression tree to format
+///
+/// # Returns
+/// Formatted source, without a trailing newline
+///
+/// # Examples
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use jaloxc::expr::{Expr, LiteralValue};
+/// use jaloxc::formatter::format;
+/// use jaloxc::parser::Parser;
+/// use jaloxc::scanner::Scanner;
+///
+/// let tokens = Scanner::new("1+2*( 3 ,4 )?[\"a\",]:{1:-2}").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+/// assert_eq!(format(&expr), "1 + 2 * (3, 4) ? [\"a\"] : {1: -2}");
+///
+/// // Formatting is idempotent: formatted source formats to itself
+/// let parse = |source: &str| Parser::new(Scanner::new(source).scan_tokens().clone()).parse().unwrap();
+/// for source in [
+///     "1+2*( 3 ,4 )?[\"a\",]:{1:-2}",
+///     "- -1 - - 2",
+///     "true?1:false?2:3",
+///     "\"tab\\t ${1 + 2} \\\"q\\\"\"",
+///     "0..10 == [1,[2,{}]][0]",
+///     "2**3**2 << 1 | 4 & 5 ^ 6 % 7",
+/// ] {
+///     let once = format(&parse(source));
+///     assert_eq!(format(&parse(&once)), once);
+/// }
+///
+/// // Large floats keep a fraction so they don't scan back as integers
+/// let expr = Expr::literal(LiteralValue::Number(1e21));
+/// assert_eq!(format(&expr), "1000000000000000000000.0");
+/// assert_eq!(parse(&format(&expr)), expr);
+///
+/// // A map that contains itself is cut off where it recurs
+/// let entries = Rc::new(RefCell::new(Vec::new()));
+/// let map = LiteralValue::Map(entries.clone());
+/// entries.borrow_mut().push((LiteralValue::String("self".to_string()), map.clone()));
+/// assert_eq!(format(&Expr::literal(map)), "{\"self\": {...}}");
+/// ```
+pub fn format(expr: &Expr) -> String {
+    SourceFormatter::new().format(expr)
+}
+
+/// Renders a literal value as the source that produces it.
+///
+/// # Arguments
+/// * `value` - Literal value to render
+///
+/// # Returns
+/// Source text, with strings quoted and escaped
+pub(crate) fn literal_source(value: &LiteralValue) -> String {
+    literal_source_within(value, &mut Vec::new())
+}
+
+/// Renders a literal value as source, cutting off cycles.
+///
+/// No source produces an array or map that contains itself, so where one
+/// recurs it is rendered as `[...]` or `{...}` instead.
+///
+/// # Arguments
+/// * `value` - Literal value to render
+/// * `active` - Addresses of the containers currently being rendered
+///
+/// # Returns
+/// Source text, with strings quoted and escaped
+fn literal_source_within(value: &LiteralValue, active: &mut Vec<usize>) -> String {
+    match value {
+        // Lox has no literals for the special values, so spell out an
+        // expression that produces them
+        LiteralValue::Number(n) if n.is_nan() => "(0 / 0)".to_string(),
+        LiteralValue::Number(n) if n.is_infinite() => {
+            format!("({}1 / 0)", if *n < 0.0 { "-" } else { "" })
+        }
+        LiteralValue::Number(n) => {
+            // An integral value with more than 15 digits would scan back as
+            // a BigInt, so keep it a float with an explicit fraction
+            let text = n.to_string();
+            if !text.contains('.') && text.trim_start_matches('-').len() > 15 {
+                format!("{}.0", text)
+            } else {
+                text
+            }
+        }
+        LiteralValue::BigInt(n) => n.to_string(),
+        LiteralValue::String(s) => format!("\"{}\"", escape(s)),
+        LiteralValue::Bool(b) => b.to_string(),
+        LiteralValue::Nil => "nil".to_string(),
+        LiteralValue::Array(elements) => {
+            let address = Rc::as_ptr(elements) as usize;
+            if active.contains(&address) {
+                return "[...]".to_string();
+            }
+
+            active.push(address);
+            let parts: Vec<String> = elements
+                .borrow()
+                .iter()
+                .map(|element| literal_source_within(element, active))
+                .collect();
+            active.pop();
+            format!("[{}]", parts.join(", "))
+        }
+        LiteralValue::Map(entries) => {
+            let address = Rc::as_ptr(entries) as usize;
+            if active.contains(&address) {
+                return "{...}".to_string();
+            }
+
+            active.push(address);
+            let parts: Vec<String> = entries
+                .borrow()
+                .iter()
+                .map(|(key, value)| {
+                    format!("{}: {}", literal_source_within(key, active), literal_source_within(value, active))
+                })
+                .collect();
+            active.pop();
+            format!("{{{}}}", parts.join(", "))
+        }
+        // Parenthesized so the range stays whole wherever the literal lands,
+        // as in `(0..10)[1]`
+        LiteralValue::Range(start, end) => format!(
+            "({}..{})",
+            literal_source(&LiteralValue::Number(*start)),
+            literal_source(&LiteralValue::Number(*end))
+        ),
+    }
+}
+
+/// Escapes string contents so they scan back to the same value.
+///
+/// # Arguments
+/// * `text` - Raw string contents
+///
+/// # Returns
+/// Text using the scanner's escape sequences for quotes, backslashes,
+/// control whitespace, and a `$` that would start an interpolation
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '$' if chars.peek() == Some(&'{') => escaped.push_str("\\$"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
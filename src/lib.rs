@@ -0,0 +1,14 @@
+/// Library crate for the jaloxc Lox implementation.
+///
+/// Exposes the scanner, token definitions, parser, expression AST, and passes
+/// over the AST so they can be reused by the command-line driver and by
+/// embedding applications.
+pub mod expr;
+pub mod token;
+pub mod scanner;
+pub mod parser;
+pub mod viz;
+pub mod fold;
+pub mod formatter;
+pub mod metrics;
+pub mod explain;
@@ -1,78 +1,563 @@
 /// Main module for Lox interpreter implementation in Rust.
 /// 
 /// Handles command-line interface, file execution, and REPL functionality.
-/// Coordinates scanning and token generation from source input.
-mod expr;
-mod token;
-mod scanner;
-
+/// Coordinates scanning and parsing of source input.
 use std::{
     env, io,
     path::Path,
-    io::Write
+    io::Write,
+    time::{Duration, Instant}
 };
-use crate::scanner::Scanner;
+use jaloxc::explain;
+use jaloxc::expr::Expr;
+use jaloxc::formatter;
+use jaloxc::parser::{Parser, ParseError};
+use jaloxc::scanner::{Scanner, ScanError};
+use jaloxc::token::TokenType;
+
+/// Exit status for incorrect command-line usage
+const EXIT_USAGE: i32 = 64;
+
+/// Exit status when the program has scan or parse errors
+const EXIT_DATA_ERROR: i32 = 65;
+
+/// Exit status when the source file can't be read
+const EXIT_IO_ERROR: i32 = 74;
+
+/// Script path that stands for standard input
+const STDIN_PATH: &str = "-";
+
+/// Output format used when reporting diagnostics on stderr.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiagnosticFormat {
+    /// One `[line N] Error: ...` line per diagnostic
+    Human,
+
+    /// A single JSON array of diagnostic objects, for editor tooling
+    Json,
+}
+
+/// Settings chosen on the command line that affect how source is processed.
+#[derive(Debug, Clone, Copy)]
+struct Options {
+    /// Format used to report diagnostics
+    format: DiagnosticFormat,
+
+    /// Whether to print per-phase timings to stderr
+    time: bool,
+
+    /// Most diagnostics to print, if capped
+    max_errors: Option<usize>,
+}
+
+/// A located error from any phase, ready to be reported.
+struct Diagnostic {
+    /// Source line of the error
+    line: usize,
+
+    /// 1-based source column of the error
+    column: usize,
+
+    /// Full human-readable form, including the location prefix
+    text: String,
+
+    /// Bare description of the problem
+    message: String,
+}
+
+impl From<&ScanError> for Diagnostic {
+    fn from(error: &ScanError) -> Self {
+        Self {
+            line: error.line,
+            column: error.column,
+            text: error.to_string(),
+            message: error.message.clone(),
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        Self {
+            line: error.token.line,
+            column: error.token.column,
+            text: error.to_string(),
+            message: error.message.clone(),
+        }
+    }
+}
 
 /// Entry point for the Lox interpreter.
 /// 
 /// Parses command line arguments and dispatches to appropriate execution modes.
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    
-    match args.len() {
-        1 => run_prompt(),
-        2 => run_file(&args[1]),
-        _ => {
-            println!("Usage: rlox [script]");
-            std::process::exit(64)
+    let mut check = false;
+    let mut explain = None;
+    let mut options = Options { format: DiagnosticFormat::Human, time: false, max_errors: None };
+    let mut paths = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--diagnostics=human" => options.format = DiagnosticFormat::Human,
+            "--diagnostics=json" => options.format = DiagnosticFormat::Json,
+            "--time" => options.time = true,
+            "--stdin" => paths.push(STDIN_PATH.to_string()),
+            "--explain" => match args.next() {
+                Some(source) => explain = Some(source),
+                None => usage(),
+            },
+            "--max-errors" => match args.next().and_then(|count| count.parse().ok()) {
+                Some(count) => options.max_errors = Some(count),
+                None => usage(),
+            },
+            flag if flag.starts_with("--") => usage(),
+            _ => paths.push(arg),
+        }
+    }
+
+    if let Some(source) = explain {
+        if !paths.is_empty() || check {
+            usage();
         }
+        explain_source(&source, options);
+        return;
+    }
+
+    match (paths.as_slice(), check) {
+        ([command, path], false) if command == "fmt" => format_file(path, options),
+        ([], false) => run_prompt(options),
+        ([path], false) => run_file(path, options),
+        ([path], true) => check_file(path, options),
+        _ => usage(),
     }
 }
 
+/// Prints the command-line usage and exits with a usage error.
+fn usage() -> ! {
+    println!("Usage: rlox [--check] [--diagnostics=human|json] [--max-errors N] [--time] [script | - | --stdin]");
+    println!("       rlox fmt [--diagnostics=human|json] [--max-errors N] [--time] script | - | --stdin");
+    println!("       rlox --explain expression");
+    std::process::exit(EXIT_USAGE)
+}
+
+/// Reads a Lox source file, exiting with an I/O error if it can't be read.
+///
+/// A path of `-` reads the whole program from standard input instead,
+/// so scripts can be piped in without starting the REPL.
+///
+/// # Arguments
+/// * `path` - Path to the Lox script file, or `-` for stdin
+///
+/// # Returns
+/// The file's contents
+fn read_source(path: impl AsRef<Path>) -> String {
+    let path = path.as_ref();
+    if path == Path::new(STDIN_PATH) {
+        return io::read_to_string(io::stdin()).unwrap_or_else(|e| {
+            eprintln!("Error reading stdin: {}", e);
+            std::process::exit(EXIT_IO_ERROR);
+        });
+    }
+
+    std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Error reading file: {}", e);
+        std::process::exit(EXIT_IO_ERROR);
+    })
+}
+
 /// Executes Lox source code from a file.
 ///
+/// Exits with status 65 if the source has scan or parse errors.
+///
 /// # Arguments
 /// * `path` - Path to the Lox script file
-fn run_file(path: impl AsRef<Path>) {
-    let source = std::fs::read_to_string(path).unwrap_or_else(|e| {
-        eprintln!("Error reading file: {}", e);
-        std::process::exit(74);
-    });
+/// * `options` - Command-line settings
+fn run_file(path: impl AsRef<Path>, options: Options) {
+    if !run(&read_source(path), options) {
+        std::process::exit(EXIT_DATA_ERROR);
+    }
+}
 
-    run(&source);
+/// Validates the syntax of a Lox file without executing it.
+///
+/// Prints every error found and exits with status 65 if there were any,
+/// or 0 if the file is well-formed.
+///
+/// # Arguments
+/// * `path` - Path to the Lox script file
+/// * `options` - Command-line settings
+fn check_file(path: impl AsRef<Path>, options: Options) {
+    let source = read_source(path);
+    let valid = parse_source(&source, options).is_some();
+    std::process::exit(if valid { 0 } else { EXIT_DATA_ERROR });
+}
+
+/// Prints a Lox file in canonical formatting.
+///
+/// The formatted source goes to stdout. If the file doesn't parse, the
+/// errors are reported and the process exits with status 65. The
+/// formatter works on the expression tree, which has no place for
+/// comments, so a file with comments is refused with the same status
+/// rather than printed without them.
+///
+/// # Arguments
+/// * `path` - Path to the Lox script file
+/// * `options` - Command-line settings
+fn format_file(path: impl AsRef<Path>, options: Options) {
+    let source = read_source(path);
+    let Some(expr) = parse_source(&source, options) else {
+        std::process::exit(EXIT_DATA_ERROR);
+    };
+
+    let mut scanner = Scanner::new_with_comments(&source);
+    if let Some(comment) = scanner
+        .scan_tokens()
+        .iter()
+        .find(|token| token.token_type == TokenType::Comment)
+    {
+        eprintln!(
+            "[line {}] Error: Can't format a file with comments; they would be lost.",
+            comment.line
+        );
+        std::process::exit(EXIT_DATA_ERROR);
+    }
+
+    println!("{}", formatter::format(&expr));
+}
+
+/// Prints how an expression given on the command line groups.
+///
+/// The expression is parsed but not evaluated. Each operation is listed
+/// in the order it applies, with its precedence level. If it doesn't
+/// parse, the errors are reported and the process exits with status 65.
+///
+/// # Arguments
+/// * `source` - Lox expression to explain
+/// * `options` - Command-line settings
+fn explain_source(source: &str, options: Options) {
+    match parse_source(source, options) {
+        Some(expr) => print!("{}", explain::explain(&expr)),
+        None => std::process::exit(EXIT_DATA_ERROR),
+    }
 }
 
 /// Starts the interactive Read-Eval-Print Loop (REPL).
 ///
 /// Continuously reads user input, executes it, and prints results.
-/// Exits on Ctrl+D or when an error occurs.
-fn run_prompt() {
+/// Input that ends inside a string or block comment is continued on the
+/// next line under a `... ` prompt. Every entry is scanned independently,
+/// so an error never leaks into the next one. Exits on Ctrl+D.
+///
+/// A line starting with `:` is a command for the REPL itself rather than
+/// Lox source: `:tokens` toggles echoing each entry's tokens, `:ast`
+/// toggles echoing its parsed tree, and `:quit` exits. The tree is echoed
+/// by default, since there is no evaluator yet to print a result instead.
+///
+/// # Arguments
+/// * `options` - Command-line settings; diagnostics are always human-readable
+fn run_prompt(options: Options) {
     println!("jaloxc interpreter (exit with Ctrl+D)");
 
+    let options = Options { format: DiagnosticFormat::Human, ..options };
+    let mut show_tokens = false;
+    let mut show_ast = true;
+    let mut buffer = String::new();
+    let mut unfinished = None;
+
     loop {
-        print!("> ");
+        print!("{}", if buffer.is_empty() { "> " } else { "... " });
         io::stdout().flush().unwrap();
 
         let mut line = String::new();
 
         match io::stdin().read_line(&mut line) {
             Ok(0) | Err(_) => break,
-            Ok(_) => run(&line),
+            Ok(_) => {}
+        }
+
+        if buffer.is_empty() && line.starts_with(':') {
+            match line.trim_end() {
+                ":tokens" => {
+                    show_tokens = !show_tokens;
+                    println!("Token echo {}", if show_tokens { "on" } else { "off" });
+                }
+                ":ast" => {
+                    show_ast = !show_ast;
+                    println!("AST echo {}", if show_ast { "on" } else { "off" });
+                }
+                ":quit" => return,
+                _ => println!("Commands: :tokens, :ast, :quit"),
+            }
+            continue;
+        }
+
+        buffer.push_str(&line);
+
+        let (scanner, scan_time) = scan_source(&buffer, options);
+        if scanner.needs_more_input() {
+            unfinished = Some((scanner, scan_time));
+            continue;
+        }
+
+        unfinished = None;
+        run_entry(&buffer, scanner, scan_time, options, show_tokens, show_ast);
+        buffer.clear();
+    }
+
+    if let Some((scanner, scan_time)) = unfinished {
+        run_entry(&buffer, scanner, scan_time, options, show_tokens, show_ast);
+    }
+}
+
+/// Processes one complete REPL entry.
+///
+/// The tokens are only echoed when scanning succeeded; otherwise just the
+/// errors are printed.
+///
+/// # Arguments
+/// * `source` - Lox source code entered by the user
+/// * `scanner` - Scanner that has already scanned `source`
+/// * `scan_time` - How long scanning took
+/// * `options` - Command-line settings
+/// * `show_tokens` - Whether to print the scanned tokens first
+/// * `show_ast` - Whether to print the parsed expression tree
+fn run_entry(
+    source: &str,
+    mut scanner: Scanner,
+    scan_time: Duration,
+    options: Options,
+    show_tokens: bool,
+    show_ast: bool,
+) {
+    if show_tokens && scanner.error_count() == 0 {
+        for token in scanner.scan_tokens() {
+            println!("{}", token);
         }
     }
+
+    if let Some(expr) = parse_scanned(&mut scanner, source, scan_time, options)
+        && show_ast
+    {
+        println!("{}", expr);
+    }
 }
 
 /// Executes Lox source code.
 ///
-/// Coordinates the scanning process and outputs tokens.
+/// Coordinates scanning and parsing and prints the resulting expression
+/// tree. If either phase reported errors, only the errors are printed.
 ///
 /// # Arguments
 /// * `source` - Lox source code to Execute
-fn run(source: &str) {
+/// * `options` - Command-line settings
+///
+/// # Returns
+/// True if the source was processed without errors, false otherwise
+fn run(source: &str, options: Options) -> bool {
+    match parse_source(source, options) {
+        Some(expr) => {
+            println!("{}", expr);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Scans and parses source code, reporting any errors.
+///
+/// # Arguments
+/// * `source` - Lox source code to parse
+/// * `options` - Command-line settings
+///
+/// # Returns
+/// The parsed expression, or None if there were errors
+fn parse_source(source: &str, options: Options) -> Option<Expr> {
+    let (mut scanner, scan_time) = scan_source(source, options);
+    parse_scanned(&mut scanner, source, scan_time, options)
+}
+
+/// Scans source code and measures how long it took.
+///
+/// # Arguments
+/// * `source` - Lox source code to scan
+/// * `options` - Command-line settings; `--max-errors` caps the errors
+///   the scanner records
+///
+/// # Returns
+/// The scanner holding the tokens and errors, and the scan duration
+fn scan_source(source: &str, options: Options) -> (Scanner, Duration) {
+    let started = Instant::now();
     let mut scanner = Scanner::new(source);
+    if let Some(max) = options.max_errors {
+        scanner = scanner.with_max_errors(max);
+    }
+    scanner.scan_tokens();
+    (scanner, started.elapsed())
+}
+
+/// Parses the tokens of already-scanned source, reporting any errors.
+///
+/// Parsing is skipped when scanning failed, since the token stream is
+/// then incomplete. Diagnostics stop being collected at `--max-errors`;
+/// later errors are only counted. With `--time`, the duration of each
+/// phase that ran is printed to stderr after any diagnostics.
+///
+/// # Arguments
+/// * `scanner` - Scanner that has already scanned `source`
+/// * `source` - Lox source code the tokens came from
+/// * `scan_time` - How long scanning took
+/// * `options` - Command-line settings
+///
+/// # Returns
+/// The parsed expression, or None if there were errors
+fn parse_scanned(scanner: &mut Scanner, source: &str, scan_time: Duration, options: Options) -> Option<Expr> {
+    let mut timings = vec![("scan", scan_time)];
     let tokens = scanner.scan_tokens().clone();
 
-    for token in tokens {
-        println!("{}", token);
+    // The scanner already stopped recording errors at the cap
+    let mut diagnostics: Vec<Diagnostic> = scanner.errors().iter().map(Diagnostic::from).collect();
+    let mut hidden = scanner.error_count() - diagnostics.len();
+
+    let mut expr = None;
+    if scanner.error_count() == 0 {
+        let started = Instant::now();
+        let result = Parser::new(tokens).parse();
+        timings.push(("parse", started.elapsed()));
+
+        match result {
+            Ok(parsed) => expr = Some(parsed),
+            Err(_) if options.max_errors.is_some_and(|max| diagnostics.len() >= max) => hidden += 1,
+            Err(error) => diagnostics.push(Diagnostic::from(&error)),
+        }
+    }
+
+    // Line numbers after a `#line` directive don't index into this source
+    let context = (!scanner.remaps_lines()).then_some(source);
+    report(&diagnostics, hidden, context, options);
+    if options.time {
+        report_timings(&timings);
+    }
+    expr
+}
+
+/// Prints phase durations to stderr on a single line.
+///
+/// # Arguments
+/// * `timings` - Phase names paired with how long each took
+fn report_timings(timings: &[(&str, Duration)]) {
+    let parts: Vec<String> = timings
+        .iter()
+        .map(|(phase, elapsed)| format!("{}: {:.1}ms", phase, elapsed.as_secs_f64() * 1000.0))
+        .collect();
+    eprintln!("{}", parts.join(" "));
+}
+
+/// Writes diagnostics to stderr in the requested format.
+///
+/// Human output follows each message with the offending source line and a
+/// caret under the reported column, when the source is given, and ends
+/// with a count of the errors left out by `--max-errors`. In JSON mode an
+/// array is always printed, even when it is empty, so tools can parse the
+/// output unconditionally.
+///
+/// # Arguments
+/// * `diagnostics` - Errors to report
+/// * `hidden` - Number of further errors that weren't collected
+/// * `source` - Source text the errors refer to, if its lines can be shown
+/// * `options` - Command-line settings choosing the format
+fn report(diagnostics: &[Diagnostic], hidden: usize, source: Option<&str>, options: Options) {
+    match options.format {
+        DiagnosticFormat::Human => {
+            for diagnostic in diagnostics {
+                eprintln!("{}", diagnostic.text);
+                if let Some(source) = source {
+                    eprint!("{}", source_context(source, diagnostic.line, diagnostic.column));
+                }
+            }
+
+            if hidden > 0 {
+                eprintln!("... and {} more error{}", hidden, if hidden == 1 { "" } else { "s" });
+            }
+        }
+        DiagnosticFormat::Json => {
+            let entries: Vec<String> = diagnostics
+                .iter()
+                .map(|error| format!(
+                    "{{\"line\": {}, \"column\": {}, \"message\": \"{}\", \"severity\": \"error\"}}",
+                    error.line,
+                    error.column,
+                    json_escape(&error.message)
+                ))
+                .collect();
+            eprintln!("[{}]", entries.join(", "));
+        }
+    }
+}
+
+/// Renders the source line at a position with a caret under the column.
+///
+/// Produces output in the style of common compiler diagnostics:
+///
+/// ```text
+///    |
+/// 12 | var x = @;
+///    |         ^
+/// ```
+///
+/// Tabs before the column are copied into the caret line so the caret
+/// stays aligned however the terminal renders them.
+///
+/// # Arguments
+/// * `source` - Full source text
+/// * `line` - 1-based line number
+/// * `column` - 1-based column, in characters
+///
+/// # Returns
+/// The rendered context, or an empty string if the line doesn't exist
+fn source_context(source: &str, line: usize, column: usize) -> String {
+    // Break lines the way the scanner does, including lone `\r`s
+    let source = source.replace("\r\n", "\n").replace('\r', "\n");
+    let Some(text) = source.lines().nth(line.saturating_sub(1)) else {
+        return String::new();
+    };
+
+    let padding: String = text
+        .chars()
+        .take(column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let gutter = " ".repeat(line.to_string().len());
+
+    format!(
+        "{gutter} |\n{line} | {text}\n{gutter} | {padding}^\n",
+        gutter = gutter,
+        line = line,
+        text = text,
+        padding = padding
+    )
+}
+
+/// Escapes text for inclusion in a JSON string literal.
+///
+/// # Arguments
+/// * `text` - Raw text
+///
+/// # Returns
+/// Text with quotes, backslashes, and control characters escaped
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
     }
+    escaped
 }
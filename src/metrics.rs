@@ -0,0 +1,124 @@
+/// Size and nesting metrics for Lox expression trees.
+///
+/// Walks an `Expr` with a visitor and counts its nodes and the length of
+/// its deepest branch, for tools such as complexity linters. Every variant
+/// counts as a node, groupings included, so the metrics describe the tree
+/// the parser built rather than the source text.
+use crate::expr::{Expr, LiteralValue, Visitor};
+use crate::token::Token;
+
+/// Visitor that measures an expression tree.
+///
+/// Each visit method counts its node and returns the depth of the subtree
+/// below it, so a parent is one level deeper than its deepest child.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// Edges on the longest path from the root to a leaf; 0 for a single
+    /// node
+    pub depth: usize,
+
+    /// Total number of nodes in the tree
+    pub node_count: usize,
+}
+
+impl Metrics {
+    /// Creates an empty set of metrics.
+    ///
+    /// # Returns
+    /// New Metrics instance with nothing counted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Measures an expression tree, replacing any earlier results.
+    ///
+    /// # Arguments
+    /// * `expr` - Root of the expression tree to measure
+    pub fn measure(&mut self, expr: &Expr) {
+        self.node_count = 0;
+        self.depth = expr.accept(self);
+    }
+
+    /// Counts a node and measures its children.
+    ///
+    /// # Arguments
+    /// * `children` - The node's direct subexpressions
+    ///
+    /// # Returns
+    /// Depth of the subtree rooted at the node
+    fn node<'a>(&mut self, children: impl IntoIterator<Item = &'a Expr>) -> usize {
+        self.node_count += 1;
+        children
+            .into_iter()
+            .map(|child| child.accept(self) + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Visitor<usize> for Metrics {
+    fn visit_binary(&mut self, left: &Expr, _operator: &Token, right: &Expr) -> usize {
+        self.node([left, right])
+    }
+
+    fn visit_grouping(&mut self, expression: &Expr) -> usize {
+        self.node([expression])
+    }
+
+    fn visit_literal(&mut self, _value: &LiteralValue) -> usize {
+        self.node([])
+    }
+
+    fn visit_unary(&mut self, _operator: &Token, right: &Expr) -> usize {
+        self.node([right])
+    }
+
+    fn visit_ternary(&mut self, condition: &Expr, then_branch: &Expr, else_branch: &Expr) -> usize {
+        self.node([condition, then_branch, else_branch])
+    }
+
+    fn visit_array(&mut self, elements: &[Expr]) -> usize {
+        self.node(elements)
+    }
+
+    fn visit_index(&mut self, object: &Expr, _bracket: &Token, index: &Expr) -> usize {
+        self.node([object, index])
+    }
+
+    fn visit_map(&mut self, entries: &[(Expr, Expr)]) -> usize {
+        self.node(entries.iter().flat_map(|(key, value)| [key, value]))
+    }
+
+    fn visit_range(&mut self, start: &Expr, end: &Expr) -> usize {
+        self.node([start, end])
+    }
+}
+
+/// Measures the depth and node count of an expression tree.
+///
+/// # Arguments
+/// * `expr` - Expression tree to measure
+///
+/// # Returns
+/// The tree's metrics
+///
+/// # Examples
+/// ```
+/// use jaloxc::metrics::measure;
+/// use jaloxc::parser::Parser;
+/// use jaloxc::scanner::Scanner;
+///
+/// // `*` over a grouping of `+`, whose operands are the deepest leaves
+/// let tokens = Scanner::new("(1 + 2) * 3").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+/// let metrics = measure(&expr);
+///
+/// assert_eq!(metrics.depth, 3);
+/// assert_eq!(metrics.node_count, 6);
+/// assert_eq!((expr.depth(), expr.node_count()), (3, 6));
+/// ```
+pub fn measure(expr: &Expr) -> Metrics {
+    let mut metrics = Metrics::new();
+    metrics.measure(expr);
+    metrics
+}
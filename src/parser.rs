@@ -0,0 +1,766 @@
+/// Recursive-descent parser for Lox expressions.
+///
+/// Turns the token sequence produced by the scanner into an `Expr` tree.
+/// The grammar, from lowest to highest precedence, is:
+///
+/// ```text
+/// expression  → comma
+/// comma       → conditional ( "," conditional )*
+/// conditional → equality ( "?" expression ":" conditional )?
+/// equality    → bit_or ( ( "!=" | "==" ) bit_or )*
+/// bit_or      → bit_xor ( "|" bit_xor )*
+/// bit_xor     → bit_and ( "^" bit_and )*
+/// bit_and     → shift ( "&" shift )*
+/// shift       → comparison ( ( "<<" | ">>" ) comparison )*
+/// comparison  → range ( ( ">" | ">=" | "<" | "<=" ) range )?
+/// range       → term ( ".." term )?
+/// term        → factor ( ( "-" | "+" ) factor )*
+/// factor      → unary ( ( "/" | "*" | "%" ) unary )*
+/// unary       → ( "!" | "-" ) unary | power
+/// power       → index ( "**" unary )?
+/// index       → primary ( "[" expression "]" )*
+/// primary     → NUMBER | STRING | "true" | "false" | "nil"
+///             | "(" expression ")"
+///             | "[" ( conditional ( "," conditional )* ","? )? "]"
+///             | "{" ( conditional ":" conditional ( "," ... )* ","? )? "}"
+/// ```
+///
+/// The operator levels from `comma` to `power` aren't separate methods:
+/// they are rows of `PRECEDENCE_TABLE`, and a single precedence-climbing
+/// loop consults the table for each operator's level and grouping. Adding
+/// or moving an operator is a change to the table alone.
+///
+/// List elements use `conditional` rather than `expression` so the commas
+/// separating them aren't swallowed by the comma operator. A single
+/// trailing comma is allowed after the last element, but a list can't
+/// consist of a comma alone.
+use std::fmt;
+use crate::expr::{Expr, LiteralValue};
+use crate::token::{Literal, Token, TokenType};
+
+/// How a run of operators on the same precedence level groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+
+    /// `a ** b ** c` is `a ** (b ** c)`
+    Right,
+
+    /// `a < b < c` is an error
+    NonAssociative,
+}
+
+/// One row of the operator precedence table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrecedenceLevel {
+    /// Grammar rule the level corresponds to
+    pub name: &'static str,
+
+    /// Operators on this level
+    pub operators: &'static [TokenType],
+
+    /// How consecutive operators on this level group
+    pub associativity: Associativity,
+}
+
+/// Binary and conditional operators, from loosest to tightest binding.
+///
+/// This is the table the parser itself uses. Prefix `!` and `-` bind
+/// tighter than every level except `power`.
+pub const PRECEDENCE_TABLE: &[PrecedenceLevel] = &[
+    PrecedenceLevel {
+        name: "comma",
+        operators: &[TokenType::Comma],
+        associativity: Associativity::Left,
+    },
+    PrecedenceLevel {
+        name: "conditional",
+        operators: &[TokenType::Question],
+        associativity: Associativity::Right,
+    },
+    PrecedenceLevel {
+        name: "equality",
+        operators: &[TokenType::BangEqual, TokenType::EqualEqual],
+        associativity: Associativity::Left,
+    },
+    PrecedenceLevel {
+        name: "bit_or",
+        operators: &[TokenType::Pipe],
+        associativity: Associativity::Left,
+    },
+    PrecedenceLevel {
+        name: "bit_xor",
+        operators: &[TokenType::Caret],
+        associativity: Associativity::Left,
+    },
+    PrecedenceLevel {
+        name: "bit_and",
+        operators: &[TokenType::Ampersand],
+        associativity: Associativity::Left,
+    },
+    PrecedenceLevel {
+        name: "shift",
+        operators: &[TokenType::LessLess, TokenType::GreaterGreater],
+        associativity: Associativity::Left,
+    },
+    PrecedenceLevel {
+        name: "comparison",
+        operators: &[
+            TokenType::Greater,
+            TokenType::GreaterEqual,
+            TokenType::Less,
+            TokenType::LessEqual,
+        ],
+        associativity: Associativity::NonAssociative,
+    },
+    PrecedenceLevel {
+        name: "range",
+        operators: &[TokenType::DotDot],
+        associativity: Associativity::NonAssociative,
+    },
+    PrecedenceLevel {
+        name: "term",
+        operators: &[TokenType::Minus, TokenType::Plus],
+        associativity: Associativity::Left,
+    },
+    PrecedenceLevel {
+        name: "factor",
+        operators: &[TokenType::Slash, TokenType::Star, TokenType::Percent],
+        associativity: Associativity::Left,
+    },
+    PrecedenceLevel {
+        name: "power",
+        operators: &[TokenType::StarStar],
+        associativity: Associativity::Right,
+    },
+];
+
+/// Looks up the binding power of a binary or conditional operator.
+///
+/// Levels count from 1 for the loosest row of `PRECEDENCE_TABLE`, so a
+/// higher level binds tighter. `-` reports its binary (`term`) level.
+///
+/// # Arguments
+/// * `op` - Token type of the operator
+///
+/// # Returns
+/// The operator's level, or None if it isn't an infix operator
+///
+/// # Examples
+/// ```
+/// use jaloxc::parser::{precedence, Parser};
+/// use jaloxc::scanner::Scanner;
+/// use jaloxc::token::TokenType;
+///
+/// assert!(precedence(&TokenType::Star) > precedence(&TokenType::Plus));
+/// assert!(precedence(&TokenType::Plus) > precedence(&TokenType::EqualEqual));
+/// assert_eq!(precedence(&TokenType::Bang), None);
+///
+/// let tokens = Scanner::new("1 == 2 + 3 * 4").scan_tokens().clone();
+/// let expr = Parser::new(tokens).parse().unwrap();
+/// assert_eq!(expr.to_string(), "(== 1 (+ 2 (* 3 4)))");
+/// ```
+pub fn precedence(op: &TokenType) -> Option<u8> {
+    PRECEDENCE_TABLE
+        .iter()
+        .position(|level| level.operators.contains(op))
+        .and_then(|index| u8::try_from(index + 1).ok())
+}
+
+/// Looks up the level of an operator the parser knows is in the table.
+///
+/// # Arguments
+/// * `op` - Token type of the operator
+///
+/// # Returns
+/// The operator's level
+fn level_of(op: TokenType) -> u8 {
+    precedence(&op).expect("operator is in PRECEDENCE_TABLE")
+}
+
+/// An error encountered while parsing tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// The token at which the error was detected
+    pub token: Token,
+
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let location = match &self.token.file {
+            Some(file) => format!("{}:{}", file, self.token.line),
+            None => format!("line {}", self.token.line),
+        };
+
+        if self.token.token_type == TokenType::Eof {
+            write!(f, "[{}] Error at end: {}", location, self.message)
+        } else {
+            write!(f, "[{}] Error at '{}': {}", location, self.token.lexeme, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The parser that builds expression trees from tokens.
+pub struct Parser {
+    /// Tokens to parse, terminated by an `Eof` token
+    tokens: Vec<Token>,
+
+    /// Index of the next token to be consumed
+    current: usize,
+}
+
+impl Parser {
+    /// Creates a new parser over the given tokens.
+    ///
+    /// The lookahead helpers stop at an `Eof` token, so one is appended if
+    /// the tokens don't already end with it, as when they are empty.
+    ///
+    /// # Arguments
+    /// * `tokens` - Scanned tokens, normally ending with `Eof`
+    ///
+    /// # Returns
+    /// New Parser instance positioned at the first token
+    pub fn new(mut tokens: Vec<Token>) -> Self {
+        match tokens.last() {
+            Some(last) if last.token_type == TokenType::Eof => {}
+            Some(last) => {
+                let column = last.column + last.lexeme.chars().count();
+                let eof = Token::new(TokenType::Eof, "", None, last.line, column);
+                tokens.push(eof.with_file(last.file.clone()));
+            }
+            None => tokens.push(Token::new(TokenType::Eof, "", None, 1, 1)),
+        }
+        Self { tokens, current: 0 }
+    }
+
+    /// Parses the tokens as a single expression.
+    ///
+    /// # Returns
+    /// The parsed expression, or the first error encountered
+    pub fn parse(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.expression()?;
+        self.consume(TokenType::Eof, "Expect end of expression.")?;
+        Ok(expr)
+    }
+
+    /// Parses an expression, including comma sequences.
+    fn expression(&mut self) -> Result<Expr, ParseError> {
+        self.binary(1)
+    }
+
+    /// Parses an expression that stops before a top-level comma.
+    fn conditional(&mut self) -> Result<Expr, ParseError> {
+        self.binary(level_of(TokenType::Question))
+    }
+
+    /// Parses operators that bind at least as tightly as `min_level`.
+    ///
+    /// Operators and their grouping come from `PRECEDENCE_TABLE`, so each
+    /// loop iteration handles one operator of whatever level comes next.
+    ///
+    /// # Arguments
+    /// * `min_level` - Loosest precedence level this call may consume
+    fn binary(&mut self, min_level: u8) -> Result<Expr, ParseError> {
+        let mut expr = self.unary()?;
+
+        while let Some(level) = precedence(&self.peek().token_type).filter(|&level| level >= min_level) {
+            let operator = self.advance().clone();
+
+            if operator.token_type == TokenType::Question {
+                let then_branch = self.expression()?;
+                if !self.match_tokens(&[TokenType::Colon]) {
+                    return Err(Self::error(
+                        &operator,
+                        "Expect ':' after then branch of conditional expression.",
+                    ));
+                }
+                let else_branch = self.binary(level)?;
+                expr = Expr::ternary(expr, then_branch, else_branch);
+                continue;
+            }
+
+            let associativity = PRECEDENCE_TABLE[usize::from(level) - 1].associativity;
+            let right = match associativity {
+                Associativity::Right => self.binary(level)?,
+                Associativity::Left | Associativity::NonAssociative => self.binary(level + 1)?,
+            };
+            let is_range = operator.token_type == TokenType::DotDot;
+            expr = if is_range {
+                Expr::range(expr, right)
+            } else {
+                Expr::binary(expr, operator, right)
+            };
+
+            if associativity == Associativity::NonAssociative
+                && precedence(&self.peek().token_type) == Some(level)
+            {
+                let message = if is_range {
+                    "Ranges can't be chained."
+                } else {
+                    // `1 < 2 < 3` would compare the true/false result of
+                    // `1 < 2` with 3, which is never what the author meant
+                    "Comparisons can't be chained: 'a < b < c' would compare the \
+                     true/false result of 'a < b' with 'c'. Combine them with 'and' \
+                     instead, as in 'a < b and b < c'."
+                };
+                return Err(Self::error(self.peek(), message));
+            }
+        }
+
+        Ok(expr)
+    }
+
+    /// Parses prefix negation and logical not.
+    ///
+    /// The operand may contain `**`, which binds tighter than a prefix
+    /// operator, so `-2 ** 2` is `-(2 ** 2)`.
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.match_tokens(&[TokenType::Bang, TokenType::Minus]) {
+            let operator = self.previous().clone();
+            let right = self.binary(level_of(TokenType::StarStar))?;
+            return Ok(Expr::unary(operator, right));
+        }
+
+        self.index()
+    }
+
+    /// Parses any number of `[index]` suffixes.
+    fn index(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.primary()?;
+
+        while self.match_tokens(&[TokenType::LeftBracket]) {
+            let bracket = self.previous().clone();
+            let index = self.expression()?;
+            self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+            expr = Expr::index(expr, bracket, index);
+        }
+
+        Ok(expr)
+    }
+
+    /// Parses literals, groupings, and collection literals.
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        if self.match_tokens(&[TokenType::False]) {
+            return Ok(Expr::literal(LiteralValue::Bool(false)));
+        }
+        if self.match_tokens(&[TokenType::True]) {
+            return Ok(Expr::literal(LiteralValue::Bool(true)));
+        }
+        if self.match_tokens(&[TokenType::Nil]) {
+            return Ok(Expr::literal(LiteralValue::Nil));
+        }
+
+        if self.match_tokens(&[TokenType::Number, TokenType::String]) {
+            let token = self.previous();
+            let value = match &token.literal {
+                Some(Literal::Number(n)) => LiteralValue::Number(*n),
+                Some(Literal::BigInt(n)) => LiteralValue::BigInt(**n),
+                Some(Literal::Str(s)) => LiteralValue::String(s.clone()),
+                // The scanner leaves out the value of a number it couldn't
+                // read, so it can't be mistaken for a real one
+                _ if token.token_type == TokenType::Number => {
+                    return Err(Self::error(token, "Malformed number."));
+                }
+                _ => LiteralValue::Nil,
+            };
+            return Ok(Expr::literal(value));
+        }
+
+        if self.match_tokens(&[TokenType::LeftParen]) {
+            let expr = self.expression()?;
+            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+            return Ok(Expr::grouping(expr));
+        }
+
+        if self.match_tokens(&[TokenType::LeftBracket]) {
+            return self.array();
+        }
+
+        if self.match_tokens(&[TokenType::LeftBrace]) {
+            return self.map();
+        }
+
+        Err(Self::error(self.peek(), "Expect expression."))
+    }
+
+    /// Parses the elements of an array literal after its `[`.
+    fn array(&mut self) -> Result<Expr, ParseError> {
+        let mut elements = Vec::new();
+
+        if !self.check(TokenType::RightBracket) {
+            loop {
+                elements.push(self.conditional()?);
+                if !self.match_tokens(&[TokenType::Comma]) || self.check(TokenType::RightBracket) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(TokenType::RightBracket, "Expect ']' after array elements.")?;
+        Ok(Expr::array(elements))
+    }
+
+    /// Parses the entries of a map literal after its `{`.
+    fn map(&mut self) -> Result<Expr, ParseError> {
+        let mut entries = Vec::new();
+
+        if !self.check(TokenType::RightBrace) {
+            loop {
+                let key = self.conditional()?;
+                self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+                let value = self.conditional()?;
+                entries.push((key, value));
+                if !self.match_tokens(&[TokenType::Comma]) || self.check(TokenType::RightBrace) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+        Ok(Expr::map(entries))
+    }
+
+    /// Consumes the current token if it has any of the given types.
+    ///
+    /// # Arguments
+    /// * `types` - Token types to accept
+    ///
+    /// # Returns
+    /// True if a token was matched and consumed, false otherwise
+    fn match_tokens(&mut self, types: &[TokenType]) -> bool {
+        if types.iter().any(|&token_type| self.check(token_type)) {
+            self.advance();
+            return true;
+        }
+        false
+    }
+
+    /// Consumes the current token, requiring it to have the given type.
+    ///
+    /// # Arguments
+    /// * `token_type` - The expected token type
+    /// * `message` - Error description if the token doesn't match
+    ///
+    /// # Returns
+    /// The consumed token, or an error pointing at the unexpected token
+    fn consume(&mut self, token_type: TokenType, message: &str) -> Result<&Token, ParseError> {
+        if self.check(token_type) {
+            return Ok(self.advance());
+        }
+        Err(Self::error(self.peek(), message))
+    }
+
+    /// Checks whether the current token has the given type.
+    ///
+    /// Never matches past the end of input, except for `Eof` itself.
+    ///
+    /// # Arguments
+    /// * `token_type` - The token type to test for
+    ///
+    /// # Returns
+    /// True if the current token has that type, false otherwise
+    fn check(&self, token_type: TokenType) -> bool {
+        if self.is_at_end() {
+            return token_type == TokenType::Eof;
+        }
+        self.peek().token_type == token_type
+    }
+
+    /// Consumes the current token.
+    ///
+    /// # Returns
+    /// The token that was consumed
+    fn advance(&mut self) -> &Token {
+        if !self.is_at_end() {
+            self.current += 1;
+        }
+        self.previous()
+    }
+
+    /// Checks if the parser has reached the `Eof` token.
+    ///
+    /// # Returns
+    /// True if no tokens remain to be parsed, false otherwise
+    fn is_at_end(&self) -> bool {
+        self.peek().token_type == TokenType::Eof
+    }
+
+    /// Looks at the current token without consuming it.
+    ///
+    /// # Returns
+    /// The current token
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current]
+    }
+
+    /// Returns the most recently consumed token.
+    ///
+    /// # Returns
+    /// The previous token
+    fn previous(&self) -> &Token {
+        &self.tokens[self.current - 1]
+    }
+
+    /// Builds an error located at the given token.
+    ///
+    /// # Arguments
+    /// * `token` - Token where the error was detected
+    /// * `message` - Error description
+    ///
+    /// # Returns
+    /// A new ParseError
+    fn error(token: &Token, message: &str) -> ParseError {
+        ParseError {
+            token: token.clone(),
+            message: message.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    /// Builds a token on line 1 with a literal for numbers and strings.
+    fn token(token_type: TokenType, lexeme: &str) -> Token {
+        let literal = match token_type {
+            TokenType::Number => Some(Literal::Number(lexeme.parse().unwrap())),
+            TokenType::String => Some(Literal::Str(lexeme.to_string())),
+            _ => None,
+        };
+        Token::new(token_type, lexeme, literal, 1, 1)
+    }
+
+    /// Builds a parser over the given tokens followed by `Eof`.
+    fn parser(tokens: &[(TokenType, &str)]) -> Parser {
+        let mut tokens: Vec<Token> = tokens
+            .iter()
+            .map(|&(token_type, lexeme)| token(token_type, lexeme))
+            .collect();
+        tokens.push(token(TokenType::Eof, ""));
+        Parser::new(tokens)
+    }
+
+    /// Scans and parses source text.
+    fn parse(source: &str) -> Result<Expr, ParseError> {
+        Parser::new(Scanner::new(source).scan_tokens().clone()).parse()
+    }
+
+    /// `1 + 2`
+    fn sum() -> Parser {
+        parser(&[(TokenType::Number, "1"), (TokenType::Plus, "+"), (TokenType::Number, "2")])
+    }
+
+    #[test]
+    fn peek_returns_current_token_without_consuming() {
+        let parser = sum();
+        assert_eq!(parser.peek().lexeme.as_ref(), "1");
+        assert_eq!(parser.peek().lexeme.as_ref(), "1");
+    }
+
+    #[test]
+    fn previous_returns_last_consumed_token() {
+        let mut parser = sum();
+        parser.advance();
+        parser.advance();
+        assert_eq!(parser.previous().token_type, TokenType::Plus);
+        assert_eq!(parser.peek().lexeme.as_ref(), "2");
+    }
+
+    #[test]
+    fn check_tests_current_token_only() {
+        let parser = sum();
+        assert!(parser.check(TokenType::Number));
+        assert!(!parser.check(TokenType::Plus));
+        assert!(!parser.check(TokenType::Eof));
+    }
+
+    #[test]
+    fn check_matches_only_eof_at_end() {
+        let parser = parser(&[]);
+        assert!(parser.check(TokenType::Eof));
+        assert!(!parser.check(TokenType::Number));
+    }
+
+    #[test]
+    fn match_tokens_consumes_only_on_a_match() {
+        let mut parser = sum();
+        assert!(!parser.match_tokens(&[TokenType::Plus, TokenType::Minus]));
+        assert_eq!(parser.current, 0);
+        assert!(parser.match_tokens(&[TokenType::String, TokenType::Number]));
+        assert_eq!(parser.current, 1);
+        assert!(parser.match_tokens(&[TokenType::Minus, TokenType::Plus]));
+        assert_eq!(parser.previous().token_type, TokenType::Plus);
+    }
+
+    #[test]
+    fn advance_returns_consumed_token_and_stops_at_eof() {
+        let mut parser = sum();
+        assert_eq!(parser.advance().lexeme.as_ref(), "1");
+        assert_eq!(parser.advance().lexeme.as_ref(), "+");
+        assert_eq!(parser.advance().lexeme.as_ref(), "2");
+        assert!(parser.is_at_end());
+
+        // Advancing at the end keeps returning the last real token
+        assert_eq!(parser.advance().lexeme.as_ref(), "2");
+        assert_eq!(parser.current, 3);
+        assert!(parser.is_at_end());
+    }
+
+    #[test]
+    fn consume_returns_expected_token() {
+        let mut parser = sum();
+        let consumed = parser.consume(TokenType::Number, "Expect number.").unwrap();
+        assert_eq!(consumed.lexeme.as_ref(), "1");
+        assert_eq!(parser.current, 1);
+    }
+
+    #[test]
+    fn consume_reports_unexpected_token_without_consuming() {
+        let mut parser = sum();
+        let error = parser.consume(TokenType::RightParen, "Expect ')'.").unwrap_err();
+        assert_eq!(error.token.token_type, TokenType::Number);
+        assert_eq!(error.message, "Expect ')'.");
+        assert_eq!(parser.current, 0);
+    }
+
+    #[test]
+    fn is_at_end_only_at_eof() {
+        let mut parser = sum();
+        for _ in 0..3 {
+            assert!(!parser.is_at_end());
+            parser.advance();
+        }
+        assert!(parser.is_at_end());
+    }
+
+    #[test]
+    fn empty_tokens_get_an_eof() {
+        let mut parser = Parser::new(vec![]);
+        assert!(parser.is_at_end());
+        assert_eq!(parser.peek().token_type, TokenType::Eof);
+
+        let error = parser.parse().unwrap_err();
+        assert_eq!(error.message, "Expect expression.");
+        assert_eq!(error.to_string(), "[line 1] Error at end: Expect expression.");
+    }
+
+    #[test]
+    fn missing_eof_is_appended() {
+        let mut parser = Parser::new(vec![token(TokenType::Number, "12")]);
+        assert_eq!(parser.tokens.len(), 2);
+        assert_eq!(parser.tokens[1].token_type, TokenType::Eof);
+        assert_eq!(parser.tokens[1].column, 3);
+        assert_eq!(parser.parse().unwrap(), Expr::literal(LiteralValue::Number(12.0)));
+    }
+
+    #[test]
+    fn conditional_nests_to_the_right() {
+        let expr = parse("true ? 1 : false ? 2 : 3").unwrap();
+        let expected = Expr::ternary(
+            Expr::literal(LiteralValue::Bool(true)),
+            Expr::literal(LiteralValue::Number(1.0)),
+            Expr::ternary(
+                Expr::literal(LiteralValue::Bool(false)),
+                Expr::literal(LiteralValue::Number(2.0)),
+                Expr::literal(LiteralValue::Number(3.0)),
+            ),
+        );
+        assert_eq!(expr, expected);
+        assert_eq!(expr.to_string(), "(?: true 1 (?: false 2 3))");
+    }
+
+    #[test]
+    fn conditional_without_colon_is_reported_at_question_mark() {
+        let error = parse("1 ? 2").unwrap_err();
+        assert_eq!(error.token.token_type, TokenType::Question);
+        assert_eq!(error.token.column, 3);
+        assert_eq!(error.message, "Expect ':' after then branch of conditional expression.");
+    }
+
+    #[test]
+    fn comma_sequence_is_left_associative() {
+        assert_eq!(parse("1, 2, 3").unwrap().to_string(), "(, (, 1 2) 3)");
+    }
+
+    #[test]
+    fn comma_does_not_swallow_array_elements() {
+        let expr = parse("[1, 2]").unwrap();
+        let expected = Expr::array(vec![
+            Expr::literal(LiteralValue::Number(1.0)),
+            Expr::literal(LiteralValue::Number(2.0)),
+        ]);
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn comma_does_not_swallow_map_entries() {
+        let expr = parse("{1: 2, 3: 4}").unwrap();
+        let number = |n| Expr::literal(LiteralValue::Number(n));
+        let expected = Expr::map(vec![(number(1.0), number(2.0)), (number(3.0), number(4.0))]);
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn grouped_comma_sequence_is_a_single_element() {
+        assert_eq!(parse("[(1, 2), 3]").unwrap().to_string(), "(array (group (, 1 2)) 3)");
+    }
+
+    #[test]
+    fn chained_comparison_is_reported_at_second_operator() {
+        let error = parse("1 < 2 < 3").unwrap_err();
+        assert_eq!(error.token.token_type, TokenType::Less);
+        assert_eq!(error.token.column, 7);
+        assert!(error.message.starts_with("Comparisons can't be chained"));
+        assert!(error.message.contains("'a < b and b < c'"));
+    }
+
+    #[test]
+    fn single_comparison_still_parses() {
+        assert_eq!(parse("1 < 2 == 3 >= 4").unwrap().to_string(), "(== (< 1 2) (>= 3 4))");
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        let expr = parse("2 ** 3 ** 2").unwrap();
+        assert_eq!(expr.to_string(), "(** 2 (** 3 2))");
+        assert_eq!(
+            crate::fold::fold_constants(expr),
+            Expr::literal(LiteralValue::Number(512.0))
+        );
+    }
+
+    #[test]
+    fn trailing_comma_is_allowed_in_arrays() {
+        assert_eq!(parse("[1, 2,]").unwrap(), parse("[1, 2]").unwrap());
+        assert_eq!(parse("[1,]").unwrap().to_string(), "(array 1)");
+    }
+
+    #[test]
+    fn trailing_comma_is_allowed_in_maps() {
+        assert_eq!(parse("{1: 2, 3: 4,}").unwrap(), parse("{1: 2, 3: 4}").unwrap());
+        assert_eq!(parse("{1: 2,}").unwrap().to_string(), "(map 1 2)");
+    }
+
+    #[test]
+    fn lone_comma_is_not_a_list() {
+        for source in ["[,]", "{,}", "[1,,]", "{1: 2,,}"] {
+            let error = parse(source).unwrap_err();
+            assert_eq!(error.token.token_type, TokenType::Comma, "{}", source);
+            assert_eq!(error.message, "Expect expression.", "{}", source);
+        }
+    }
+
+    #[test]
+    fn errors_after_line_directive_use_its_location() {
+        let error = parse("#line 10 \"gen.lox\"\n1 + )").unwrap_err();
+        assert_eq!(error.to_string(), "[gen.lox:10] Error at ')': Expect expression.");
+    }
+}
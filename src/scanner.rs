@@ -2,25 +2,161 @@
 /// 
 /// Transforms source code into a sequence of tokens by scanning characters
 /// and recognizing language patterns (keywords, literals, operators, etc.).
-use std::collections::HashMap;
+///
+/// # String interpolation
+/// A string containing `${...}` segments is desugared at scan time into a
+/// concatenation, so the parser needs no special support for it. For example
+/// `"Hello ${name}!"` produces the tokens
+///
+/// ```text
+/// String("Hello ") Plus LeftParen Identifier(name) RightParen Plus String("!")
+/// ```
+///
+/// The embedded expression is wrapped in parentheses so it binds as a single
+/// operand, and every fragment is emitted (possibly empty) so the result is
+/// always a string concatenation. Fragment lexemes are the raw source slices
+/// surrounding the interpolation. Use `\$` to write a literal `$` before `{`.
+///
+/// # Line directives
+/// Generated code can point diagnostics back at its original source with a
+/// directive on a line of its own, starting in the first column:
+///
+/// ```text
+/// #line 42 "orig.lox"
+/// ```
+///
+/// The line after the directive is numbered 42, and errors from then on
+/// name `orig.lox`. The file name is optional; without it the current name
+/// is kept. Any other use of `#` is an unexpected character.
+///
+/// # File names
+/// A scanner made with `new_with_file` tags every token and error with the
+/// given file name, so programs assembled from several files, as with
+/// `scan_files`, still report `foo.lox:12`. A `#line` directive naming a
+/// file overrides it from that point on.
+///
+/// # Memory
+/// The scanner keeps one copy of the source, as a `String` indexed by byte
+/// offset, and decodes a character at a time rather than expanding the
+/// text into a `Vec<char>`. Lexemes are not borrowed from the source:
+/// each is sliced out and copied into an `Rc<str>`, shared between equal
+/// identifiers, keywords and operators, so tokens don't keep the scanner
+/// or its source alive.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::rc::Rc;
 use crate::token::{Token, TokenType, Literal};
 
+/// An error encountered while scanning source code.
+///
+/// Scanning doesn't stop at the first error; every problem is recorded
+/// and the scanner carries on so all of them can be reported together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanError {
+    /// The source line number where the error was found
+    pub line: usize,
+
+    /// The 1-based column of the character where the error was detected
+    pub column: usize,
+
+    /// File being scanned, as given to the scanner or named by the most
+    /// recent `#line` directive, if any
+    pub file: Option<Rc<str>>,
+
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "[{}:{}] Error: {}", file, self.line, self.message),
+            None => write!(f, "[line {}] Error: {}", self.line, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// Scanner state at the start of the `scan_token` call that produced a
+/// token, kept so scanning can later resume from that point.
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    /// Byte offset where the call started
+    offset: usize,
+
+    /// Line number at that offset
+    line: usize,
+
+    /// Number of characters already consumed on that line
+    column: usize,
+}
+
 /// The lexical scanner that processes source code into tokens.
 pub struct Scanner {
-    /// Source code as character vector for easier indexing
-    source: Vec<char>,
+    /// Source code, indexed by byte offset; tokens copy their lexemes out
+    /// of it
+    source: String,
     
     /// List of tokens generated during scanning
     tokens: Vec<Token>,
+
+    /// Where each token in `tokens` was scanned from, used by `rescan_range`
+    marks: Vec<Mark>,
+
+    /// Interned lexemes shared by identifier, keyword and operator tokens
+    symbols: HashSet<Rc<str>>,
+
+    /// Errors recorded during scanning
+    errors: Vec<ScanError>,
+
+    /// Most errors to record, if capped
+    max_errors: Option<usize>,
+
+    /// Errors found, including any past `max_errors` that weren't recorded
+    error_count: usize,
+
+    /// Whether the source ended inside a string or comment
+    incomplete: bool,
+
+    /// Whether comments are emitted as `Comment` tokens instead of skipped
+    keep_comments: bool,
+
+    /// Keyword table replacing the standard one, if any
+    keywords: Option<HashMap<String, TokenType>>,
+
+    /// Number of columns a tab character advances
+    tab_width: usize,
+
+    /// Whether `scan_tokens` has run to completion
+    scanned: bool,
+
+    /// Whether a `#line` directive has renumbered the source
+    remapped: bool,
+
+    /// Current file name: the scanned file's, or the one set by the most
+    /// recent `#line` directive
+    file: Option<Rc<str>>,
+
+    /// File name given at construction, restored whenever scanning restarts
+    source_file: Option<Rc<str>>,
     
-    /// Start position of current lexeme being scanned
+    /// Byte offset of the start of the current lexeme being scanned
     start: usize,
     
-    /// Current scanning position in source
+    /// Byte offset of the current scanning position in source
     current: usize,
     
     /// Current line number in source
-    line: usize
+    line: usize,
+
+    /// Number of characters consumed on the current line
+    column: usize,
+
+    /// 1-based column where the current lexeme starts
+    start_column: usize,
 }
 
 impl Scanner {
@@ -33,33 +169,551 @@ impl Scanner {
     /// New Scanner instance initialized to start scanning
     pub fn new(source: &str) -> Self {
         Self {
-            source: source.chars().collect(),
+            source: source.to_string(),
             tokens: Vec::new(),
+            marks: Vec::new(),
+            symbols: HashSet::new(),
+            errors: Vec::new(),
+            max_errors: None,
+            error_count: 0,
+            incomplete: false,
+            keep_comments: false,
+            keywords: None,
+            tab_width: 1,
+            scanned: false,
+            remapped: false,
+            file: None,
+            source_file: None,
             start: 0,
             current: 0,
             line: 1,
+            column: 0,
+            start_column: 1,
         }
     }
 
-     /// Scans all tokens from the source code.
+    /// Creates a scanner that keeps comments for tooling.
+    ///
+    /// Line (`//`) and block (`/* */`) comments are emitted as
+    /// `TokenType::Comment` tokens whose lexeme is the full comment text,
+    /// delimiters included, positioned where the comment starts. A scanner
+    /// made with `new` skips comments, which is what the parser expects.
+    ///
+    /// # Arguments
+    /// * `source` - The Lox source code to scan
+    ///
+    /// # Returns
+    /// New Scanner instance that emits comment tokens
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    /// use jaloxc::token::TokenType;
+    ///
+    /// let mut scanner = Scanner::new_with_comments("1 // one\n+ 2");
+    /// let tokens = scanner.scan_tokens();
+    ///
+    /// assert_eq!(tokens[1].token_type, TokenType::Comment);
+    /// assert_eq!(&*tokens[1].lexeme, "// one");
+    /// assert_eq!((tokens[1].line, tokens[1].column), (1, 3));
+    /// assert_eq!(tokens[2].token_type, TokenType::Plus);
+    /// ```
+    pub fn new_with_comments(source: &str) -> Self {
+        Self {
+            keep_comments: true,
+            ..Self::new(source)
+        }
+    }
+
+    /// Creates a scanner with its own keyword table.
+    ///
+    /// The map replaces the standard English keywords entirely: a word is
+    /// a keyword only if it appears in the map, and anything else scans as
+    /// an `Identifier`. Keywords mapped to `True`, `False` or `Nil` still
+    /// carry their literal value. This lets a dialect use localized
+    /// keywords; `new` keeps the standard table.
+    ///
+    /// # Arguments
+    /// * `source` - The Lox source code to scan
+    /// * `keywords` - Keyword spellings and the token types they produce
+    ///
+    /// # Returns
+    /// New Scanner instance using the given keywords
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashMap;
+    /// use jaloxc::scanner::Scanner;
+    /// use jaloxc::token::{Literal, TokenType};
+    ///
+    /// let keywords = HashMap::from([
+    ///     ("imprime".to_string(), TokenType::Print),
+    ///     ("si".to_string(), TokenType::If),
+    ///     ("verdadero".to_string(), TokenType::True),
+    /// ]);
+    /// let mut scanner = Scanner::new_with_keywords("si verdadero imprime print", keywords);
+    /// let types: Vec<TokenType> = scanner.scan_tokens().iter().map(|t| t.token_type).collect();
+    ///
+    /// assert_eq!(
+    ///     types,
+    ///     [TokenType::If, TokenType::True, TokenType::Print, TokenType::Identifier, TokenType::Eof]
+    /// );
+    /// assert_eq!(scanner.scan_tokens()[1].literal, Some(Literal::Bool(true)));
+    /// ```
+    pub fn new_with_keywords(source: &str, keywords: HashMap<String, TokenType>) -> Self {
+        Self {
+            keywords: Some(keywords),
+            ..Self::new(source)
+        }
+    }
+
+    /// Creates a scanner for source read from a named file.
+    ///
+    /// Every token and error records the file name, so diagnostics can say
+    /// which file they came from.
+    ///
+    /// # Arguments
+    /// * `source` - The Lox source code to scan
+    /// * `file` - Name of the file the source was read from
+    ///
+    /// # Returns
+    /// New Scanner instance that tags its output with the file name
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    ///
+    /// let mut scanner = Scanner::new_with_file("1 +\n@", "foo.lox");
+    /// let tokens = scanner.scan_tokens();
+    ///
+    /// assert_eq!(tokens[0].file.as_deref(), Some("foo.lox"));
+    /// assert_eq!(scanner.errors()[0].to_string(), "[foo.lox:2] Error: Unexpected character");
+    /// ```
+    pub fn new_with_file(source: &str, file: &str) -> Self {
+        let file: Rc<str> = Rc::from(file);
+        Self {
+            file: Some(file.clone()),
+            source_file: Some(file),
+            ..Self::new(source)
+        }
+    }
+
+    /// Sets how many columns a tab character counts for.
+    ///
+    /// By default a tab is one column, like any other character. Setting
+    /// a wider tab makes reported columns match an editor that displays
+    /// tabs at that width. Line numbers are unaffected.
+    ///
+    /// # Arguments
+    /// * `width` - Columns per tab; values below 1 are treated as 1
+    ///
+    /// # Returns
+    /// The scanner, counting tabs at the given width
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    ///
+    /// let mut scanner = Scanner::new("\t1 +\t2").with_tab_width(4);
+    /// let columns: Vec<usize> = scanner.scan_tokens().iter().map(|t| t.column).collect();
+    ///
+    /// assert_eq!(columns, [5, 7, 12, 13]);
+    /// ```
+    pub fn with_tab_width(mut self, width: usize) -> Self {
+        self.tab_width = width.max(1);
+        self
+    }
+
+    /// Caps the number of errors the scanner records.
+    ///
+    /// Scanning still runs to the end of the source, and errors past the
+    /// cap are counted, but they aren't built or stored, so a badly broken
+    /// file can't pile up an unbounded list.
+    ///
+    /// # Arguments
+    /// * `max` - Most errors to record
+    ///
+    /// # Returns
+    /// The scanner, recording at most `max` errors
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    ///
+    /// let mut scanner = Scanner::new("@ # ` ~").with_max_errors(2);
+    /// scanner.scan_tokens();
+    ///
+    /// assert_eq!(scanner.errors().len(), 2);
+    /// assert_eq!(scanner.error_count(), 4);
+    /// ```
+    pub fn with_max_errors(mut self, max: usize) -> Self {
+        self.max_errors = Some(max);
+        self
+    }
+
+    /// Scans all tokens from the source code.
     ///
     /// Processes the entire source string, generating tokens until EOF is reached.
+    /// Only the first call scans; later calls return the same tokens, so
+    /// the `Eof` token is never appended twice.
+    ///
+    /// Scanning never panics, whatever the input. Source that ends in the
+    /// middle of a token, such as an open string or block comment, is
+    /// reported as a scan error and still ends with `Eof`.
     ///
     /// # Returns
     /// Reference to the vector of scanned tokens
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    /// use jaloxc::token::TokenType;
+    ///
+    /// // Every prefix of a program, cut at each character
+    /// let program = "/* c */ \"a${1 + \"b\"}\" + \"\"\"x\"\"\" + 1_000.5 # // end";
+    /// for (cut, _) in program.char_indices() {
+    ///     let mut scanner = Scanner::new(&program[..cut]);
+    ///     let tokens = scanner.scan_tokens();
+    ///     assert_eq!(tokens.last().unwrap().token_type, TokenType::Eof);
+    /// }
+    ///
+    /// let mut scanner = Scanner::new("\"abc");
+    /// scanner.scan_tokens();
+    /// assert_eq!(scanner.errors()[0].message, "Unterminated string");
+    /// ```
     pub fn scan_tokens(&mut self) -> &Vec<Token> {
+        if self.scanned {
+            return &self.tokens;
+        }
+
         while !self.is_at_end() {
-            self.start = self.current;
-            self.scan_token();
+            self.scan_marked();
         }
-        
+
+        self.push_eof();
+        self.scanned = true;
+        &self.tokens
+    }
+
+    /// Applies an edit to the source and updates the tokens to match.
+    ///
+    /// Only the region around the edit is scanned again. Scanning restarts
+    /// one token before the first token the edit can affect, and stops at
+    /// the first old token after the edit that it reaches in the same state,
+    /// at the same column; the old tokens from there on are kept with their
+    /// lines shifted. Every token that starts before the edit is rescanned
+    /// whole, so an edit inside a string or comment rescans that string or
+    /// comment. The result is the same as scanning the edited source from
+    /// scratch.
+    ///
+    /// Falls back to a full rescan if `scan_tokens` hasn't run yet, if the
+    /// previous scan reported errors, or if a `#line` directive renumbered
+    /// the lines, either before the edit or in the rescanned region.
+    ///
+    /// # Arguments
+    /// * `edit` - Byte range of the current source to replace
+    /// * `new_text` - Text to put in its place
+    ///
+    /// # Panics
+    /// If `edit` is out of bounds or doesn't fall on character boundaries,
+    /// like `String::replace_range`.
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    ///
+    /// let mut scanner = Scanner::new("1 +\n2 * x");
+    /// scanner.scan_tokens();
+    ///
+    /// // Replace `1` with a grouping spread over two lines
+    /// scanner.rescan_range(0..1, "(3 -\n4)");
+    ///
+    /// let mut fresh = Scanner::new("(3 -\n4) +\n2 * x");
+    /// assert_eq!(scanner.scan_tokens(), fresh.scan_tokens());
+    ///
+    /// // An inserted `#line` directive renumbers everything after it
+    /// scanner.rescan_range(0..0, "#line 10 \"gen.lox\"\n");
+    ///
+    /// let mut fresh = Scanner::new("#line 10 \"gen.lox\"\n(3 -\n4) +\n2 * x");
+    /// assert_eq!(scanner.scan_tokens(), fresh.scan_tokens());
+    /// let tokens = scanner.scan_tokens();
+    /// let x = &tokens[tokens.len() - 2];
+    /// assert_eq!((x.line, x.file.as_deref()), (12, Some("gen.lox")));
+    /// ```
+    pub fn rescan_range(&mut self, edit: Range<usize>, new_text: &str) {
+        let mut source = std::mem::take(&mut self.source);
+        source.replace_range(edit.clone(), new_text);
+
+        if !self.scanned || self.error_count > 0 || self.remapped {
+            self.load(source);
+            self.scan_tokens();
+            return;
+        }
+
+        // The last token starting before the edit may run into it, and the
+        // one before that may have looked ahead into it
+        let affected = self.marks.partition_point(|mark| mark.offset < edit.start);
+        let mut restart = 0;
+        if affected > 0 {
+            restart = self.first_of_group(affected - 1);
+            if restart > 0 {
+                restart = self.first_of_group(restart - 1);
+            }
+        }
+
+        let mut old_tokens = self.tokens.split_off(restart);
+        let mut old_marks = self.marks.split_off(restart);
+        let resume = match restart {
+            0 => Mark { offset: 0, line: 1, column: 0 },
+            _ => old_marks[0],
+        };
+
+        self.source = source;
+        self.current = resume.offset;
+        self.line = resume.line;
+        self.column = resume.column;
+
+        let inserted_end = edit.start + new_text.len();
+        loop {
+            if self.current >= inserted_end {
+                let old_offset = self.current - new_text.len() + edit.len();
+                let sync = old_marks.partition_point(|mark| mark.offset < old_offset);
+                if let Some(mark) = old_marks.get(sync)
+                    && mark.offset == old_offset
+                    && mark.column == self.column
+                {
+                    // Kept tokens would miss the renumbering and file name
+                    // of a directive scanned just now
+                    if self.remapped {
+                        let source = std::mem::take(&mut self.source);
+                        self.load(source);
+                        self.scan_tokens();
+                        return;
+                    }
+
+                    let old_line = mark.line;
+                    for (mut token, mut mark) in old_tokens.drain(sync..).zip(old_marks.drain(sync..)) {
+                        token.line = token.line + self.line - old_line;
+                        mark.line = mark.line + self.line - old_line;
+                        mark.offset = mark.offset + new_text.len() - edit.len();
+                        self.tokens.push(token);
+                        self.marks.push(mark);
+                    }
+
+                    let end = self.marks[self.marks.len() - 1];
+                    self.current = end.offset;
+                    self.line = end.line;
+                    self.column = end.column;
+                    return;
+                }
+            }
+
+            if self.is_at_end() {
+                self.push_eof();
+                return;
+            }
+            self.scan_marked();
+        }
+    }
+
+    /// Checks whether `scan_tokens` has already run.
+    ///
+    /// # Returns
+    /// True if the tokens, ending with `Eof`, are available, false otherwise
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    ///
+    /// let mut scanner = Scanner::new("1 + 2");
+    /// assert!(!scanner.is_scanned());
+    ///
+    /// let count = scanner.scan_tokens().len();
+    /// assert!(scanner.is_scanned());
+    /// assert_eq!(scanner.scan_tokens().len(), count);
+    /// ```
+    pub fn is_scanned(&self) -> bool {
+        self.scanned
+    }
+
+    /// Lazily scans tokens from the source code.
+    ///
+    /// Each call to `next` lexes just enough input to produce one token,
+    /// ending with the `Eof` token and then `None`. Tokens are handed out
+    /// as they are produced instead of being accumulated: the scanner
+    /// forgets each token once it has been handed out. The two APIs can be
+    /// mixed, since `scan_tokens` only returns the tokens that haven't been
+    /// handed out yet, so there is exactly one `Eof` between them.
+    ///
+    /// # Returns
+    /// Iterator yielding tokens on demand
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    /// use jaloxc::token::TokenType;
+    ///
+    /// let mut scanner = Scanner::new("1 + \"${2}\"");
+    /// let first: Vec<_> = scanner.tokens().take(3).collect();
+    /// let rest = scanner.scan_tokens().clone();
+    ///
+    /// let mut fresh = Scanner::new("1 + \"${2}\"");
+    /// assert_eq!([first, rest].concat(), *fresh.scan_tokens());
+    ///
+    /// // Once the iterator has handed out `Eof`, nothing is left over
+    /// let mut scanner = Scanner::new("1 + 2");
+    /// let eofs = scanner.tokens().filter(|token| token.token_type == TokenType::Eof).count();
+    /// assert_eq!(eofs, 1);
+    /// assert!(scanner.scan_tokens().is_empty());
+    /// ```
+    pub fn tokens(&mut self) -> impl Iterator<Item = Token> + '_ {
+        TokenStream { scanner: self, next: 0 }
+    }
+
+    /// Returns the errors recorded while scanning.
+    ///
+    /// # Returns
+    /// Slice of scan errors in the order they were found
+    pub fn errors(&self) -> &[ScanError] {
+        &self.errors
+    }
+
+    /// Counts the errors found while scanning.
+    ///
+    /// # Returns
+    /// Number of errors, including any past the `with_max_errors` cap that
+    /// `errors` doesn't hold
+    pub fn error_count(&self) -> usize {
+        self.error_count
+    }
+
+    /// Checks whether the source ended in the middle of a token.
+    ///
+    /// True when scanning hit EOF inside a string, an interpolation, or a
+    /// block comment, meaning more input could still complete it. The REPL
+    /// uses this to ask for a continuation line instead of failing.
+    ///
+    /// # Returns
+    /// True if the source was cut off mid-token, false otherwise
+    pub fn needs_more_input(&self) -> bool {
+        self.incomplete
+    }
+
+    /// Checks whether a `#line` directive renumbered the source.
+    ///
+    /// Once it has, token and error lines no longer match line numbers in
+    /// the scanned text, so callers shouldn't use them to quote it.
+    ///
+    /// # Returns
+    /// True if any `#line` directive was applied, false otherwise
+    pub fn remaps_lines(&self) -> bool {
+        self.remapped
+    }
+
+    /// Scans the source and hands back either the tokens or the errors.
+    ///
+    /// Consumes the scanner, running `scan_tokens` first if it hasn't been
+    /// called yet, so failures can be propagated with `?`.
+    ///
+    /// # Returns
+    /// The scanned tokens, or every error recorded if scanning failed
+    pub fn into_result(mut self) -> Result<Vec<Token>, Vec<ScanError>> {
+        self.scan_tokens();
+
+        if self.error_count == 0 {
+            Ok(self.tokens)
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    /// Prepares the scanner to scan a new source from the beginning.
+    ///
+    /// Tokens, errors and position are discarded, but the buffers holding
+    /// the source, tokens and interned lexemes keep their capacity, so one
+    /// scanner can work through many small files without reallocating.
+    /// Settings chosen at construction, such as the tab width or keyword
+    /// table, are kept. Scanning after a reset gives the same result as a
+    /// fresh scanner with those settings.
+    ///
+    /// # Arguments
+    /// * `source` - The Lox source code to scan next
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    /// use jaloxc::token::TokenType;
+    ///
+    /// let mut scanner = Scanner::new("1 + 2");
+    /// assert_eq!(scanner.scan_tokens().len(), 4);
+    ///
+    /// scanner.reset("\"a\"\n@");
+    /// let types: Vec<TokenType> = scanner.scan_tokens().iter().map(|t| t.token_type).collect();
+    /// assert_eq!(types, [TokenType::String, TokenType::Eof]);
+    /// assert_eq!(scanner.scan_tokens()[1].line, 2);
+    /// assert_eq!(scanner.errors().len(), 1);
+    /// ```
+    pub fn reset(&mut self, source: &str) {
+        let mut buffer = std::mem::take(&mut self.source);
+        buffer.clear();
+        buffer.push_str(source);
+        self.load(buffer);
+    }
+
+    /// Replaces the source and discards everything scanned from the old one.
+    ///
+    /// Interned symbols are kept, since the new source is likely to reuse
+    /// them.
+    ///
+    /// # Arguments
+    /// * `source` - New source code
+    fn load(&mut self, source: String) {
+        self.source = source;
+        self.tokens.clear();
+        self.marks.clear();
+        self.errors.clear();
+        self.error_count = 0;
+        self.incomplete = false;
+        self.scanned = false;
+        self.remapped = false;
+        self.file = self.source_file.clone();
+        self.start = 0;
+        self.current = 0;
+        self.line = 1;
+        self.column = 0;
+        self.start_column = 1;
+    }
+
+    /// Scans the next lexeme, recording where its tokens came from.
+    fn scan_marked(&mut self) {
+        let mark = Mark { offset: self.current, line: self.line, column: self.column };
+        self.begin_token();
+        self.scan_token();
+        self.marks.resize(self.tokens.len(), mark);
+    }
+
+    /// Appends the `Eof` token at the current position.
+    fn push_eof(&mut self) {
+        self.marks.push(Mark { offset: self.current, line: self.line, column: self.column });
         self.tokens.push(Token::new(
             TokenType::Eof,
-            "".to_string(),
+            "",
             None,
-            self.line
-        ));
-        &self.tokens
+            self.line,
+            self.column + 1
+        ).with_file(self.file.clone()));
+    }
+
+    /// Finds the first token scanned by the same `scan_token` call as another.
+    ///
+    /// # Arguments
+    /// * `index` - Index of a token in `tokens`
+    ///
+    /// # Returns
+    /// Index of the first token sharing that token's mark
+    fn first_of_group(&self, index: usize) -> usize {
+        let offset = self.marks[index].offset;
+        self.marks.partition_point(|mark| mark.offset < offset)
     }
 
     /// Processes a single token based on current scanner state.
@@ -73,12 +727,19 @@ impl Scanner {
             ')' => self.add_token(TokenType::RightParen),
             '{' => self.add_token(TokenType::LeftBrace),
             '}' => self.add_token(TokenType::RightBrace),
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
             ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
+            '%' => self.add_token(TokenType::Percent),
+            '&' => self.add_token(TokenType::Ampersand),
+            '|' => self.add_token(TokenType::Pipe),
+            '^' => self.add_token(TokenType::Caret),
+            '?' => self.add_token(TokenType::Question),
+            ':' => self.add_token(TokenType::Colon),
+            '#' if self.start_column == 1 && self.at_line_directive() => {
+                self.line_directive();
+            }
             '!' => {
                 let tok = if self.match_char('=') {
                     TokenType::BangEqual
@@ -98,6 +759,8 @@ impl Scanner {
             '<' => {
                 let tok = if self.match_char('=') {
                     TokenType::LessEqual
+                } else if self.match_char('<') {
+                    TokenType::LessLess
                 } else {
                     TokenType::Less
                 };
@@ -106,36 +769,122 @@ impl Scanner {
             '>' => {
                 let tok = if self.match_char('=') {
                     TokenType::GreaterEqual
+                } else if self.match_char('>') {
+                    TokenType::GreaterGreater
                 } else {
                     TokenType::Greater
                 };
                 self.add_token(tok);
             }
+            '.' => {
+                let tok = if self.peek() == '.' && self.peek_next() == '.' {
+                    self.advance();
+                    self.advance();
+                    TokenType::Ellipsis
+                } else if self.match_char('.') {
+                    TokenType::DotDot
+                } else {
+                    TokenType::Dot
+                };
+                self.add_token(tok);
+            }
+            '-' => {
+                let tok = if self.match_char('=') {
+                    TokenType::MinusEqual
+                } else if self.match_char('-') {
+                    TokenType::MinusMinus
+                } else {
+                    TokenType::Minus
+                };
+                self.add_token(tok);
+            }
+            '+' => {
+                let tok = if self.match_char('=') {
+                    TokenType::PlusEqual
+                } else if self.match_char('+') {
+                    TokenType::PlusPlus
+                } else {
+                    TokenType::Plus
+                };
+                self.add_token(tok);
+            }
+            '*' => {
+                let tok = if self.match_char('=') {
+                    TokenType::StarEqual
+                } else if self.match_char('*') {
+                    TokenType::StarStar
+                } else {
+                    TokenType::Star
+                };
+                self.add_token(tok);
+            }
             '/' => {
                 if self.match_char('/') {
-                    while self.peek() != '\n' && !self.is_at_end() {
+                    while !self.at_line_end() {
                         self.advance();
                     }
+                    self.comment(self.line);
                 } else if self.match_char('*') {
                     self.block_comment();
+                } else if self.match_char('=') {
+                    self.add_token(TokenType::SlashEqual);
                 } else {
                     self.add_token(TokenType::Slash);
                 }
             }
-            ' ' | '\r' | '\t' => {/* Ignore whitespace */ }
-            '\n' => self.line += 1,
+            ' ' | '\r' | '\t' | '\n' => {/* Ignore whitespace */ }
             '"' => self.string(),
             c if c.is_ascii_digit() => self.number(),
-            c if c.is_ascii_alphabetic() || c == '_' => self.identifier(),
+            c if c.is_alphabetic() || c == '_' => self.identifier(),
             _ => self.error("Unexpected character"),
         }
     }
         
+    /// Checks whether the text after a `#` is the word `line` and a space.
+    ///
+    /// # Returns
+    /// True if a `#line` directive starts here, false otherwise
+    fn at_line_directive(&self) -> bool {
+        self.source[self.current..]
+            .strip_prefix("line")
+            .is_some_and(|rest| rest.starts_with([' ', '\t']))
+    }
+
+    /// Processes a `#line` directive after its `#`.
+    ///
+    /// Consumes the rest of the line. A well-formed directive renumbers the
+    /// following line and optionally changes the file name used in errors;
+    /// anything else is reported as malformed and otherwise ignored.
+    fn line_directive(&mut self) {
+        let directive_start = self.current + "line".len();
+        while !self.at_line_end() {
+            self.advance();
+        }
+
+        let text = &self.source[directive_start..self.current];
+        match parse_line_directive(text) {
+            Some((line, file)) => {
+                if let Some(file) = file {
+                    self.file = Some(Rc::from(file));
+                }
+                // The newline ending the directive brings the count to `line`
+                self.line = line - 1;
+                self.remapped = true;
+            }
+            None => self.error(
+                "Malformed #line directive; expected '#line N' or '#line N \"file\"'",
+            ),
+        }
+    }
+
     /// Processes block comments, including nested comments.
     ///
     /// Handles both single-line (`//`) and multi-line (`/* */`) comments.
     /// Supports arbitrary nesting depth for multi-line comments.
+    /// An unterminated comment is reported at EOF together with the line
+    /// on which the outermost `/*` was opened.
     fn block_comment(&mut self) {
+        let opening_line = self.line;
         let mut nesting = 1;
         while nesting > 0 && !self.is_at_end() {
             if self.peek() == '/' && self.peek_next() == '*' {
@@ -147,40 +896,239 @@ impl Scanner {
                 self.advance();
                 nesting -= 1;
             } else {
-                if self.peek() == '\n' {
-                    self.line += 1;
-                }
                 self.advance();
             }
         }
 
         if nesting > 0 {
-            self.error("Unterminated block comment");
+            self.unterminated(&format!(
+                "Unterminated block comment opened on line {}",
+                opening_line
+            ));
+        } else {
+            self.comment(opening_line);
+        }
+    }
+
+    /// Emits the comment just scanned, if comments are being kept.
+    ///
+    /// # Arguments
+    /// * `line` - Line on which the comment starts
+    fn comment(&mut self, line: usize) {
+        if self.keep_comments {
+            let text = self.lexeme(self.start, self.current);
+            let token = Token::new(TokenType::Comment, text, None, line, self.start_column);
+            self.tokens.push(token.with_file(self.file.clone()));
         }
     }
 
     /// Processes string literals.
     ///
-    /// Collects characters between double quotes, handling escape sequences
-    /// and tracking newlines within strings.
+    /// Collects characters between double quotes, handling escape sequences,
+    /// `${...}` interpolation segments, and tracking newlines within strings.
     fn string(&mut self) {
+        if self.peek() == '"' && self.peek_next() == '"' {
+            self.advance();
+            self.advance();
+            self.triple_quoted_string();
+            return;
+        }
+
+        let mut fragment_start = self.start;
+        let mut fragment_column = self.start_column;
+        let mut value = String::new();
+
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            match self.advance() {
+                '\\' => {
+                    if let Some(c) = self.escape_sequence() {
+                        value.push(c);
+                    }
+                }
+                '$' if self.peek() == '{' => {
+                    self.advance();
+                    self.start_column = fragment_column;
+                    self.push_token(
+                        TokenType::String,
+                        self.lexeme(fragment_start, self.current - 2),
+                        Some(Literal::Str(std::mem::take(&mut value)))
+                    );
+                    self.start_column = self.column - 1;
+                    self.push_token(TokenType::Plus, "+".into(), None);
+                    self.push_token(TokenType::LeftParen, "(".into(), None);
+
+                    if !self.interpolation() {
+                        return;
+                    }
+
+                    self.start_column = self.column;
+                    self.push_token(TokenType::RightParen, ")".into(), None);
+                    self.push_token(TokenType::Plus, "+".into(), None);
+                    fragment_start = self.current;
+                    fragment_column = self.column + 1;
+                }
+                c => value.push(c),
+            }
+        }
+
+        if self.is_at_end() {
+            self.unterminated("Unterminated string");
+            return;
+        }
+
+        self.advance();
+
+        self.start_column = fragment_column;
+        self.push_token(
+            TokenType::String,
+            self.lexeme(fragment_start, self.current),
+            Some(Literal::Str(value))
+        );
+    }
+
+    /// Processes the escape sequence following a backslash in a string.
+    ///
+    /// Supports `\n`, `\t`, `\r`, `\\`, `\"`, `\$` (a literal dollar sign,
+    /// used to suppress interpolation) and `\u{...}` (any Unicode code
+    /// point, written as 1 to 6 hex digits). Any other character after a
+    /// backslash is not an escape: the backslash is kept as written and the
+    /// character is scanned normally, so strings such as `"C:\dir"` keep the
+    /// meaning they had before escapes were recognized.
+    ///
+    /// # Returns
+    /// The escaped character, or None if the sequence was invalid
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    /// use jaloxc::token::Literal;
+    ///
+    /// let mut scanner = Scanner::new(r#""\u{e9}t\u{E9} \u{1F600}""#);
+    /// let literal = scanner.scan_tokens()[0].literal.clone();
+    /// assert_eq!(literal, Some(Literal::Str("été 😀".to_string())));
+    ///
+    /// let mut scanner = Scanner::new(r#""C:\dir\$x""#);
+    /// let literal = scanner.scan_tokens()[0].literal.clone();
+    /// assert_eq!(literal, Some(Literal::Str(r"C:\dir$x".to_string())));
+    /// assert!(scanner.errors().is_empty());
+    ///
+    /// for invalid in [r#""\u{D800}""#, r#""\u{110000}""#, r#""\u{41""#, r#""\u41""#, r#""\u{}""#] {
+    ///     let mut scanner = Scanner::new(invalid);
+    ///     scanner.scan_tokens();
+    ///     assert_eq!(scanner.errors().len(), 1, "{}", invalid);
+    /// }
+    /// ```
+    fn escape_sequence(&mut self) -> Option<char> {
+        if self.is_at_end() {
+            return None;
+        }
+
+        let escaped = match self.peek() {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '\\' => '\\',
+            '"' => '"',
+            '$' => '$',
+            'u' => {
+                self.advance();
+                return self.unicode_escape();
+            }
+            _ => return Some('\\'),
+        };
+        self.advance();
+        Some(escaped)
+    }
+
+    /// Processes the braced code point of a `\u{...}` escape.
+    ///
+    /// Surrogates and values above `10FFFF` aren't characters, so they are
+    /// reported like a malformed escape.
+    ///
+    /// # Returns
+    /// The escaped character, or None if the escape was invalid
+    fn unicode_escape(&mut self) -> Option<char> {
+        if !self.match_char('{') {
+            self.error("Expect '{' after '\\u'");
+            return None;
+        }
+
+        let digits_start = self.current;
+        while self.peek().is_ascii_hexdigit() {
+            self.advance();
+        }
+        let digits_end = self.current;
+
+        if !self.match_char('}') {
+            self.error("Unterminated unicode escape; expected '}'");
+            return None;
+        }
+        if !(1..=6).contains(&(digits_end - digits_start)) {
+            self.error("Unicode escape must have 1 to 6 hex digits");
+            return None;
+        }
+
+        let code = u32::from_str_radix(&self.source[digits_start..digits_end], 16).ok()?;
+        let escaped = char::from_u32(code);
+        if escaped.is_none() {
+            self.error(&format!("Invalid code point U+{:X} in unicode escape", code));
+        }
+        escaped
+    }
+
+    /// Scans the tokens of an embedded `${...}` expression.
+    ///
+    /// Runs the regular token scanner until the `}` matching the opening
+    /// `${` is consumed, tracking nested braces along the way.
+    ///
+    /// # Returns
+    /// True if the interpolation was closed, false if EOF was reached first
+    fn interpolation(&mut self) -> bool {
+        let mut depth = 0;
+        loop {
+            if self.is_at_end() {
+                self.unterminated("Unterminated string interpolation");
+                return false;
+            }
+
+            match self.peek() {
+                '}' if depth == 0 => {
+                    self.advance();
+                    return true;
+                }
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+
+            self.begin_token();
+            self.scan_token();
+        }
+    }
+
+    /// Processes triple-quoted string literals (`"""..."""`).
+    ///
+    /// Interior newlines are preserved verbatim and a lone `"` does not
+    /// terminate the literal; only a run of three quotes closes it.
+    fn triple_quoted_string(&mut self) {
+        while !self.is_at_end() {
+            if self.peek() == '"' && self.peek_next() == '"'
+                && self.source[self.current..].starts_with("\"\"\"") {
+                break;
             }
             self.advance();
         }
 
         if self.is_at_end() {
-            self.error("Unterminated string");
+            self.unterminated("Unterminated triple-quoted string");
             return;
         }
 
+        self.advance();
+        self.advance();
         self.advance();
 
-        let value: String = self.source[self.start + 1..self.current - 1]
-            .iter()
-            .collect();
+        let value = self.source[self.start + 3..self.current - 3].to_string();
 
         self.add_token_with_literal(
             TokenType::String,
@@ -191,61 +1139,106 @@ impl Scanner {
     /// Processes numeric literals.
     ///
     /// Handles both integers and floating-point numbers with decimal points.
-    /// Validates number format and converts to f64 representation.
+    /// Underscores may separate digits on either side of the decimal point
+    /// (`1_000_000`, `3.141_592`); they are kept in the lexeme but stripped
+    /// before conversion. A text such as `_5` starts an identifier, not a
+    /// number. Validates number format and converts to f64 representation.
+    ///
+    /// A fraction needs at least one digit after the dot. `123.0` is a
+    /// single `Number(123.0)`, but `123.` scans as `Number(123)` followed by
+    /// `Dot`, as in the reference implementation. This is deliberate: it
+    /// keeps `123.sqrt()` meaning a method access on the number `123`
+    /// rather than depending on what follows the dot.
+    ///
+    /// An integer with more than 15 significant digits, past the point
+    /// where an f64 can hold every integer exactly, becomes a
+    /// `Literal::BigInt` instead so no digits are lost. One too large even
+    /// for an i128 falls back to an f64.
+    ///
+    /// A literal too large to represent as a finite f64 is reported as out
+    /// of range. The token is still emitted, but without a literal value,
+    /// so a parser that reads it anyway reports it as malformed instead of
+    /// quietly using a made-up number.
     fn number(&mut self) {
-        while self.peek().is_ascii_digit() {
-            self.advance();
-        }
+        self.digits(self.start);
 
+        // Only consume the dot when a digit follows; otherwise leave it for
+        // the next token.
         if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.advance();
-            while self.peek().is_ascii_digit() {
-                self.advance();
-            }
+            self.digits(self.current);
         }
 
-        let num_str: String = self.source[self.start..self.current].iter().collect();
-        let value = num_str.parse::<f64>().unwrap_or_else(|_|{
-            self.error(&format!("Invalid number: {}", num_str));
-            0.0
-        });
+        let num_str = self.source[self.start..self.current].replace('_', "");
 
-        self.add_token_with_literal(
-            TokenType::Number, 
-            Some(Literal::Number(value))
-        );
+        if num_str.trim_start_matches('0').len() > 15
+            && !num_str.contains('.')
+            && let Ok(value) = num_str.parse::<i128>()
+        {
+            self.add_token_with_literal(TokenType::Number, Some(Literal::BigInt(Box::new(value))));
+            return;
+        }
+
+        let literal = match num_str.parse::<f64>() {
+            Ok(value) if value.is_finite() => Some(Literal::Number(value)),
+            Ok(_) => {
+                self.error("Number literal out of range");
+                None
+            }
+            Err(_) => {
+                self.error(&format!("Invalid number: {}", num_str));
+                None
+            }
+        };
+
+        self.add_token_with_literal(TokenType::Number, literal);
+    }
+
+    /// Consumes a run of digits, optionally separated by underscores.
+    ///
+    /// Reports an error if an underscore is doubled or ends the run, since
+    /// separators must sit between two digits.
+    ///
+    /// # Arguments
+    /// * `run_start` - Byte offset of the first digit in the run
+    fn digits(&mut self, run_start: usize) {
+        while self.peek().is_ascii_digit() || self.peek() == '_' {
+            self.advance();
+        }
+
+        let run = &self.source[run_start..self.current];
+        if run.contains("__") || run.ends_with('_') {
+            let message = format!(
+                "Misplaced '_' in number literal '{}'; underscores must separate digits",
+                &self.source[self.start..self.current]
+            );
+            self.error(&message);
+        }
     }
 
     /// Processes identifiers and keywords.
     ///
     /// Collects alphanumeric sequences and checks against keyword table.
     /// Handles special literal values (true, false, nil) appropriately.
+    ///
+    /// Identifiers follow Unicode rules: they start with an alphabetic
+    /// character or `_` and continue with alphanumerics, `_` or combining
+    /// marks, so `café` and `名前` are valid names whether the accent is
+    /// precomposed or written as `e` plus U+0301. Keywords are the exact
+    /// spellings in the scanner's keyword table, which is the standard
+    /// English one unless the scanner was made with `new_with_keywords`.
     fn identifier(&mut self) {
-        while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+        while is_identifier_continue(self.peek()) {
             self.advance();
         }
 
-        let text: String = self.source[self.start..self.current].iter().collect();
-
-        let token_type = match text.as_str() {
-            "and" => TokenType::And,
-            "class" => TokenType::Class,
-            "else" => TokenType::Else,
-            "false" => TokenType::False,
-            "fun" => TokenType::Fun,
-            "for" => TokenType::For,
-            "if" => TokenType::If,
-            "nil" => TokenType::Nil,
-            "or" => TokenType::Or,
-            "print" => TokenType::Print,
-            "return" => TokenType::Return,
-            "super" => TokenType::Super,
-            "this" => TokenType::This,
-            "true" => TokenType::True,
-            "var" => TokenType::Var,
-            "while" => TokenType::While,
-            _ => TokenType::Identifier,
+        let text = &self.source[self.start..self.current];
+
+        let keyword = match &self.keywords {
+            Some(keywords) => keywords.get(text).copied(),
+            None => TokenType::keyword_from_str(text),
         };
+        let token_type = keyword.unwrap_or(TokenType::Identifier);
 
         let literal = match token_type {
             TokenType::False => Some(Literal::Bool(false)),
@@ -257,13 +1250,55 @@ impl Scanner {
         self.add_token_with_literal(token_type, literal);
     }
 
+    /// Marks the current position as the start of a new lexeme.
+    fn begin_token(&mut self) {
+        self.start = self.current;
+        self.start_column = self.column + 1;
+    }
+
     /// Advances the scanner by one character.
     ///
+    /// Keeps the line and column counters up to date, so callers never
+    /// need to track newlines themselves. A tab advances the column by
+    /// the scanner's tab width.
+    ///
+    /// `\n`, `\r\n` and a lone `\r` each end a line, so Unix, Windows and
+    /// classic Mac files number lines alike. The `\r` of a `\r\n` pair
+    /// takes no column; the `\n` after it ends the line.
+    ///
     /// # Returns
     /// The character at the current position before advancing
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::scanner::Scanner;
+    ///
+    /// for source in ["1\n  2\n3", "1\r\n  2\r\n3", "1\r  2\r3"] {
+    ///     let mut scanner = Scanner::new(source);
+    ///     let positions: Vec<(usize, usize)> =
+    ///         scanner.scan_tokens().iter().map(|t| (t.line, t.column)).collect();
+    ///     assert_eq!(positions, [(1, 1), (2, 3), (3, 1), (3, 2)], "{:?}", source);
+    /// }
+    /// ```
     fn advance(&mut self) -> char {
-        let c = self.source[self.current];
-        self.current += 1;
+        // Past the end there is nothing to consume; stepping over the
+        // placeholder would leave `current` outside the source
+        if self.is_at_end() {
+            return '\0';
+        }
+
+        let c = self.peek();
+        self.current += c.len_utf8();
+        match c {
+            // The `\r` of a `\r\n` pair leaves the line break to the `\n`
+            '\r' if self.peek() == '\n' => {}
+            '\n' | '\r' => {
+                self.line += 1;
+                self.column = 0;
+            }
+            '\t' => self.column += self.tab_width,
+            _ => self.column += 1,
+        }
         c
     }
 
@@ -278,10 +1313,10 @@ impl Scanner {
     /// # Returns
     /// True if matched and advanced, false otherwise
     fn match_char(&mut self, expected: char) -> bool {
-        if self.is_at_end() || self.source[self.current] != expected {
+        if self.is_at_end() || self.peek() != expected {
             return false;
         }
-        self.current += 1;
+        self.advance();
         true
     }
 
@@ -290,7 +1325,7 @@ impl Scanner {
     /// # Returns
     /// Current character if available, null character otherwise
     fn peek(&self) -> char{
-        if self.is_at_end() {'\0'} else {self.source[self.current]}
+        self.source.get(self.current..).and_then(|rest| rest.chars().next()).unwrap_or('\0')
     }
 
     /// Peeks at the next character without consuming it.
@@ -298,7 +1333,15 @@ impl Scanner {
     /// # Returns
     /// Next character if available, null character otherwise
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {'\0'} else {self.source[self.current + 1]}
+        self.source.get(self.current..).and_then(|rest| rest.chars().nth(1)).unwrap_or('\0')
+    }
+
+    /// Checks whether the scanner is at a line break or the end of source.
+    ///
+    /// # Returns
+    /// True before `\n`, `\r` or EOF, false otherwise
+    fn at_line_end(&self) -> bool {
+        matches!(self.peek(), '\n' | '\r') || self.is_at_end()
     }
 
     /// Checks if scanner has reached end of source.
@@ -323,17 +1366,482 @@ impl Scanner {
     /// * `token_type` - The type of token to add
     /// * `literal` - Optional literal value for the token
     fn add_token_with_literal(&mut self, token_type: TokenType, literal: Option<Literal>) {
-        let text: String = self.source[self.start..self.current].iter().collect();
-        self.tokens.push(Token::new(token_type, text, literal, self.line));
+        let text = match token_type {
+            TokenType::String | TokenType::Number => self.lexeme(self.start, self.current),
+            _ => self.intern(self.start, self.current),
+        };
+        self.push_token(token_type, text, literal);
     }
 
-    /// Reports an error during scanning.
+    /// Adds a token whose lexeme is supplied explicitly.
     ///
-    /// Prints error message to stderr with line number context.
+    /// Used for tokens that don't correspond to a single contiguous lexeme,
+    /// such as the fragments and operators produced by string interpolation.
+    ///
+    /// # Arguments
+    /// * `token_type` - The type of token to add
+    /// * `lexeme` - Text recorded as the token's lexeme
+    /// * `literal` - Optional literal value for the token
+    fn push_token(&mut self, token_type: TokenType, lexeme: Rc<str>, literal: Option<Literal>) {
+        let token = Token::new(token_type, lexeme, literal, self.line, self.start_column);
+        self.tokens.push(token.with_file(self.file.clone()));
+    }
+
+    /// Copies the source text between two byte offsets.
+    ///
+    /// # Arguments
+    /// * `start` - Byte offset of the first character
+    /// * `end` - Byte offset one past the last character
+    ///
+    /// # Returns
+    /// The source text in the given range
+    fn lexeme(&self, start: usize, end: usize) -> Rc<str> {
+        Rc::from(&self.source[start..end])
+    }
+
+    /// Returns the interned copy of the source text between two byte offsets.
+    ///
+    /// The first occurrence of a piece of text allocates it; every later
+    /// occurrence shares that allocation.
+    ///
+    /// # Arguments
+    /// * `start` - Byte offset of the first character
+    /// * `end` - Byte offset one past the last character
+    ///
+    /// # Returns
+    /// Shared lexeme for the given range
+    fn intern(&mut self, start: usize, end: usize) -> Rc<str> {
+        let text = &self.source[start..end];
+        if let Some(symbol) = self.symbols.get(text) {
+            return Rc::clone(symbol);
+        }
+
+        let symbol: Rc<str> = Rc::from(text);
+        self.symbols.insert(Rc::clone(&symbol));
+        symbol
+    }
+
+    /// Records an error found during scanning.
+    ///
+    /// The error is tagged with the current line and column and kept for
+    /// later reporting through `errors` or `into_result`. Past the
+    /// `with_max_errors` cap it is only counted.
     ///
     /// # Arguments
     /// * `message` - Error description
     fn error(&mut self, message: &str) {
-        eprintln!("[line {}] Error: {}", self.line, message);
+        self.error_count += 1;
+        if self.max_errors.is_some_and(|max| self.errors.len() >= max) {
+            return;
+        }
+
+        self.errors.push(ScanError {
+            line: self.line,
+            column: self.column.max(1),
+            file: self.file.clone(),
+            message: message.to_string(),
+        });
+    }
+
+    /// Records an error for a string or comment cut off by EOF.
+    ///
+    /// # Arguments
+    /// * `message` - Error description
+    fn unterminated(&mut self, message: &str) {
+        self.incomplete = true;
+        self.error(message);
+    }
+}
+
+/// Scans several files as one program.
+///
+/// Each file is scanned with its path as the file name, and the tokens
+/// are concatenated in order behind a single final `Eof`. Errors from
+/// every file are collected; a file that can't be read is reported as an
+/// error at line 0.
+///
+/// # Arguments
+/// * `paths` - Files to scan, in program order
+///
+/// # Returns
+/// The combined tokens, or every error found if any file failed
+///
+/// # Examples
+/// ```
+/// use std::path::PathBuf;
+/// use jaloxc::scanner::scan_files;
+///
+/// let dir = std::env::temp_dir();
+/// let (a, b) = (dir.join("scan_files_a.lox"), dir.join("scan_files_b.lox"));
+/// std::fs::write(&a, "1 +").unwrap();
+/// std::fs::write(&b, "\n2").unwrap();
+///
+/// let tokens = scan_files(&[a.clone(), b.clone()]).unwrap();
+/// let files: Vec<PathBuf> = tokens.iter().map(|t| PathBuf::from(&**t.file.as_ref().unwrap())).collect();
+///
+/// assert_eq!(files, [a.clone(), a, b.clone(), b]);
+/// assert_eq!(tokens[2].line, 2);
+/// ```
+pub fn scan_files(paths: &[PathBuf]) -> Result<Vec<Token>, Vec<ScanError>> {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    for path in paths {
+        let file = path.display().to_string();
+        match std::fs::read_to_string(path) {
+            Ok(source) => match Scanner::new_with_file(&source, &file).into_result() {
+                Ok(scanned) => {
+                    // Only the last file's end marks the end of the program
+                    tokens.pop();
+                    tokens.extend(scanned);
+                }
+                Err(scan_errors) => errors.extend(scan_errors),
+            },
+            Err(e) => errors.push(ScanError {
+                line: 0,
+                column: 0,
+                file: Some(Rc::from(file)),
+                message: format!("Could not read file: {}", e),
+            }),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    if tokens.is_empty() {
+        tokens.push(Token::new(TokenType::Eof, "", None, 1, 1));
+    }
+    Ok(tokens)
+}
+
+/// Checks whether a character can continue an identifier.
+///
+/// Approximates Unicode's XID_Continue without a property table:
+/// alphanumerics and `_`, plus the combining mark blocks, which hold the
+/// accents of decomposed text. Marks that `char::is_alphabetic` already
+/// covers, such as most vowel signs, need no special case.
+///
+/// # Arguments
+/// * `c` - Character to test
+///
+/// # Returns
+/// True if the character may appear after the first one in a name
+fn is_identifier_continue(c: char) -> bool {
+    c.is_alphanumeric()
+        || c == '_'
+        || matches!(
+            c,
+            '\u{0300}'..='\u{036F}'
+                | '\u{1AB0}'..='\u{1AFF}'
+                | '\u{1DC0}'..='\u{1DFF}'
+                | '\u{20D0}'..='\u{20FF}'
+                | '\u{FE20}'..='\u{FE2F}'
+        )
+}
+
+/// Iterator returned by `Scanner::tokens`.
+///
+/// Hands out the scanner's pending tokens through a cursor and scans the
+/// next lexeme only once all of them are out. Handed-out tokens are
+/// removed from the scanner before scanning more and when the iterator is
+/// dropped.
+struct TokenStream<'a> {
+    /// Scanner producing the tokens
+    scanner: &'a mut Scanner,
+
+    /// Index in the scanner's tokens of the next token to hand out
+    next: usize,
+}
+
+impl Iterator for TokenStream<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let scanner = &mut *self.scanner;
+        if self.next == scanner.tokens.len() {
+            if scanner.scanned {
+                return None;
+            }
+
+            scanner.tokens.clear();
+            scanner.marks.clear();
+            self.next = 0;
+
+            // One lexeme can produce no tokens, or several
+            while scanner.tokens.is_empty() && !scanner.is_at_end() {
+                scanner.scan_marked();
+            }
+            if scanner.tokens.is_empty() {
+                scanner.push_eof();
+                scanner.scanned = true;
+            }
+        }
+
+        let token = scanner.tokens[self.next].clone();
+        self.next += 1;
+        Some(token)
+    }
+}
+
+impl Drop for TokenStream<'_> {
+    fn drop(&mut self) {
+        self.scanner.tokens.drain(..self.next);
+        self.scanner.marks.drain(..self.next);
+    }
+}
+
+/// Parses the text of a `#line` directive that follows the word `line`.
+///
+/// # Arguments
+/// * `text` - Rest of the directive line, e.g. ` 42 "orig.lox"`
+///
+/// # Returns
+/// The new line number and optional file name, or None if malformed
+fn parse_line_directive(text: &str) -> Option<(usize, Option<&str>)> {
+    let text = text.trim();
+    let (number, rest) = text.split_once([' ', '\t']).unwrap_or((text, ""));
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let line = number.parse::<usize>().ok().filter(|&line| line > 0)?;
+
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return Some((line, None));
+    }
+
+    let file = rest.strip_prefix('"')?.strip_suffix('"')?;
+    if file.is_empty() || file.contains('"') {
+        return None;
+    }
+    Some((line, Some(file)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scans source text, panicking on scan errors.
+    fn scan(source: &str) -> Vec<Token> {
+        Scanner::new(source).into_result().unwrap()
+    }
+
+    #[test]
+    fn trailing_dot_is_not_part_of_a_number() {
+        let tokens = scan("123.");
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].token_type, TokenType::Number);
+        assert_eq!(tokens[0].lexeme.as_ref(), "123");
+        assert_eq!(tokens[0].literal, Some(Literal::Number(123.0)));
+        assert_eq!(tokens[1].token_type, TokenType::Dot);
+        assert_eq!(tokens[1].column, 4);
+        assert_eq!(tokens[2].token_type, TokenType::Eof);
+    }
+
+    #[test]
+    fn unknown_escapes_keep_the_backslash() {
+        let tokens = scan(r#""C:\dir\q" "a\tb""#);
+        assert_eq!(tokens[0].literal, Some(Literal::Str(r"C:\dir\q".to_string())));
+        assert_eq!(tokens[1].literal, Some(Literal::Str("a\tb".to_string())));
+    }
+
+    #[test]
+    fn digits_after_the_dot_make_a_fraction() {
+        let tokens = scan("123.0");
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].token_type, TokenType::Number);
+        assert_eq!(tokens[0].lexeme.as_ref(), "123.0");
+        assert_eq!(tokens[0].literal, Some(Literal::Number(123.0)));
+    }
+
+    #[test]
+    fn unicode_identifiers_keep_whole_lexemes_and_char_columns() {
+        let tokens = scan("café + 名前");
+        let summary: Vec<(TokenType, &str, usize)> = tokens
+            .iter()
+            .map(|token| (token.token_type, token.lexeme.as_ref(), token.column))
+            .collect();
+        assert_eq!(summary, [
+            (TokenType::Identifier, "café", 1),
+            (TokenType::Plus, "+", 6),
+            (TokenType::Identifier, "名前", 8),
+            (TokenType::Eof, "", 10),
+        ]);
+    }
+
+    #[test]
+    fn combining_marks_continue_an_identifier() {
+        let tokens = scan("e\u{301}x + 1");
+        assert_eq!(tokens[0].token_type, TokenType::Identifier);
+        assert_eq!(tokens[0].lexeme.as_ref(), "e\u{301}x");
+        assert_eq!(tokens[1].column, 5);
+    }
+
+    #[test]
+    fn combining_mark_cannot_start_an_identifier() {
+        let mut scanner = Scanner::new("\u{301}x");
+        scanner.scan_tokens();
+        assert_eq!(scanner.errors().len(), 1);
+        assert_eq!(scanner.errors()[0].message, "Unexpected character");
+    }
+
+    #[test]
+    fn overlong_number_literal_is_out_of_range() {
+        for source in ["9".repeat(400), format!("1{}.5", "0".repeat(400))] {
+            let mut scanner = Scanner::new(&format!("{} + 1", source));
+            let tokens = scanner.scan_tokens().clone();
+            assert_eq!(scanner.errors().len(), 1);
+            assert_eq!(scanner.errors()[0].message, "Number literal out of range");
+            assert_eq!(scanner.errors()[0].column, source.len());
+            assert_eq!(tokens.last().unwrap().token_type, TokenType::Eof);
+        }
+    }
+
+    #[test]
+    fn errors_after_line_directive_use_its_location() {
+        let mut scanner = Scanner::new("#line 10 \"gen.lox\"\n1 @ 2");
+        scanner.scan_tokens();
+        let error = &scanner.errors()[0];
+        assert_eq!((error.file.as_deref(), error.line), (Some("gen.lox"), 10));
+        assert_eq!(error.to_string(), "[gen.lox:10] Error: Unexpected character");
+    }
+
+    #[test]
+    fn mixing_tokens_and_scan_tokens_gives_one_eof() {
+        let source = "1 + \"a${2}b\" // done";
+        let expected = scan(source);
+
+        for taken in 0..=expected.len() {
+            let mut scanner = Scanner::new(source);
+            let mut tokens: Vec<Token> = scanner.tokens().take(taken).collect();
+            tokens.extend(scanner.scan_tokens().iter().cloned());
+            assert_eq!(tokens, expected, "after taking {}", taken);
+        }
+    }
+
+    #[test]
+    fn tokens_after_scan_tokens_hands_out_the_scanned_tokens() {
+        let mut scanner = Scanner::new("1 + 2");
+        let scanned = scanner.scan_tokens().clone();
+        assert_eq!(scanner.tokens().collect::<Vec<_>>(), scanned);
+        assert_eq!(scanner.tokens().count(), 0);
+    }
+
+    /// Punctuation and operator tokens, as the generator writes them.
+    const OPERATORS: &[(TokenType, &str)] = &[
+        (TokenType::LeftParen, "("), (TokenType::RightParen, ")"),
+        (TokenType::LeftBrace, "{"), (TokenType::RightBrace, "}"),
+        (TokenType::LeftBracket, "["), (TokenType::RightBracket, "]"),
+        (TokenType::Comma, ","), (TokenType::Dot, "."), (TokenType::Minus, "-"),
+        (TokenType::Plus, "+"), (TokenType::Semicolon, ";"), (TokenType::Slash, "/"),
+        (TokenType::Star, "*"), (TokenType::Percent, "%"), (TokenType::Question, "?"),
+        (TokenType::Colon, ":"), (TokenType::Ampersand, "&"), (TokenType::Pipe, "|"),
+        (TokenType::Caret, "^"), (TokenType::Bang, "!"), (TokenType::BangEqual, "!="),
+        (TokenType::Equal, "="), (TokenType::EqualEqual, "=="), (TokenType::Greater, ">"),
+        (TokenType::GreaterEqual, ">="), (TokenType::GreaterGreater, ">>"),
+        (TokenType::Less, "<"), (TokenType::LessEqual, "<="), (TokenType::LessLess, "<<"),
+        (TokenType::PlusPlus, "++"), (TokenType::MinusMinus, "--"),
+        (TokenType::PlusEqual, "+="), (TokenType::MinusEqual, "-="),
+        (TokenType::StarEqual, "*="), (TokenType::SlashEqual, "/="),
+        (TokenType::StarStar, "**"), (TokenType::DotDot, ".."), (TokenType::Ellipsis, "..."),
+    ];
+
+    /// Reserved words; their token types come from `keyword_from_str`.
+    const KEYWORDS: &[&str] = &[
+        "and", "assert", "break", "case", "catch", "class", "const", "continue", "default",
+        "do", "else", "false", "fun", "for", "if", "in", "nil", "or", "print", "return",
+        "super", "switch", "this", "true", "try", "var", "while",
+    ];
+
+    const NUMBERS: &[&str] = &["0", "7", "123", "3.25", "1_000", "0.5_5", "12345678901234567890"];
+    const IDENTIFIERS: &[&str] = &["x", "_tmp", "a1", "andy", "café", "名前"];
+    const STRINGS: &[&str] = &["\"\"", "\"hi\"", "\"a\\nb\"", "\"$5\"", "\"two\nlines\""];
+
+    /// Xorshift generator with a fixed seed, so failures reproduce.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+            items[(self.next() % items.len() as u64) as usize]
+        }
+    }
+
+    /// Generates one random token with its source text.
+    fn random_token(rng: &mut Rng) -> (TokenType, &'static str) {
+        match rng.next() % 5 {
+            0 => rng.pick(OPERATORS),
+            1 => {
+                let word = rng.pick(KEYWORDS);
+                (TokenType::keyword_from_str(word).unwrap(), word)
+            }
+            2 => (TokenType::Number, rng.pick(NUMBERS)),
+            3 => (TokenType::Identifier, rng.pick(IDENTIFIERS)),
+            _ => (TokenType::String, rng.pick(STRINGS)),
+        }
+    }
+
+    /// Checks that rendering the tokens and scanning the text gives back
+    /// the same token types, followed by `Eof`.
+    fn round_trips(tokens: &[(TokenType, &str)], separator: &str) -> bool {
+        let lexemes: Vec<&str> = tokens.iter().map(|&(_, lexeme)| lexeme).collect();
+        let Ok(scanned) = Scanner::new(&lexemes.join(separator)).into_result() else {
+            return false;
+        };
+        let mut expected: Vec<TokenType> = tokens.iter().map(|&(token_type, _)| token_type).collect();
+        expected.push(TokenType::Eof);
+        scanned.iter().map(|token| token.token_type).eq(expected)
+    }
+
+    /// Removes tokens from a failing sequence while it still fails.
+    fn shrink<'a>(mut tokens: Vec<(TokenType, &'a str)>, separator: &str) -> Vec<(TokenType, &'a str)> {
+        let mut i = 0;
+        while i < tokens.len() {
+            let mut smaller = tokens.clone();
+            smaller.remove(i);
+            if round_trips(&smaller, separator) {
+                i += 1;
+            } else {
+                tokens = smaller;
+            }
+        }
+        tokens
+    }
+
+    #[test]
+    fn random_token_sequences_round_trip() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..5000 {
+            let length = rng.next() % 16;
+            let tokens: Vec<_> = (0..length).map(|_| random_token(&mut rng)).collect();
+            let separator = rng.pick(&[" ", "  ", "\t", "\n", "\r\n"]);
+            if !round_trips(&tokens, separator) {
+                let minimal = shrink(tokens.clone(), separator);
+                panic!("{:?} joined by {:?} doesn't round-trip; minimal: {:?}", tokens, separator, minimal);
+            }
+        }
+    }
+
+    #[test]
+    fn adjacent_numbers_and_dots_round_trip() {
+        // Written without separators, where a number could swallow a dot
+        let cases: &[&[(TokenType, &str)]] = &[
+            &[(TokenType::Number, "123"), (TokenType::Dot, ".")],
+            &[(TokenType::Number, "123.0")],
+            &[(TokenType::Number, "1"), (TokenType::DotDot, ".."), (TokenType::Number, "2")],
+            &[(TokenType::Number, "1"), (TokenType::Ellipsis, "...")],
+            &[(TokenType::Dot, "."), (TokenType::Number, "5")],
+        ];
+        for tokens in cases {
+            assert!(round_trips(tokens, ""), "{:?}", tokens);
+        }
     }
 }
@@ -4,29 +4,42 @@
 /// the `Token` struct representing a scanned token, and the `Literal` enum for
 /// representing different literal value types.
 use std::fmt;
+use std::rc::Rc;
 
 /// All possible token types in the Lox language.
 /// 
 /// Categorized into single-character tokens, multi-character tokens,
 /// literals, keywords, and the special EOF marker.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TokenType {
     /// Single-character tokens
-    LeftParen, RightParen, LeftBrace, RightBrace,
+    LeftParen, RightParen, LeftBrace, RightBrace, LeftBracket, RightBracket,
     Comma, Dot, Minus, Plus, Semicolon, Slash, Star,
+    Percent, Question, Colon,
+    Ampersand, Pipe, Caret,
     
     /// One or two character tokens
     Bang, BangEqual,
     Equal, EqualEqual,
-    Greater, GreaterEqual,
-    Less, LessEqual,
+    Greater, GreaterEqual, GreaterGreater,
+    Less, LessEqual, LessLess,
+    PlusPlus, MinusMinus,
+    PlusEqual, MinusEqual, StarEqual, SlashEqual,
+    StarStar,
+    DotDot, Ellipsis,
     
     /// Literal value tokens
     Identifier, String, Number,
     
     /// Keyword tokens
-    And, Class, Else, False, Fun, For, If, Nil, Or,
-    Print, Return, Super, This, True, Var, While,
+    ///
+    /// These words are reserved and can't be used as identifiers.
+    And, Assert, Break, Case, Catch, Class, Const, Continue, Default, Do, Else,
+    False, Fun, For, If, In, Nil, Or, Print, Return, Super, Switch, This, True, Try,
+    Var, While,
+    
+    /// Comment text, only produced by a scanner that keeps comments
+    Comment,
     
     /// End-of-file marker
     Eof,
@@ -36,28 +49,44 @@ pub enum TokenType {
 /// 
 /// Contains information about the token's type, the original lexeme,
 /// any literal value it represents, and its line location in source.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     /// The type of token
     pub token_type: TokenType,
     
     /// The original text as it appeared in source code
-    pub lexeme: String,
+    ///
+    /// Shared rather than owned so that repeated identifiers, keywords and
+    /// operators can reuse a single interned allocation.
+    pub lexeme: Rc<str>,
     
     /// The interpreted value for literals (numbers, strings, etc.)
     pub literal: Option<Literal>,
     
     /// The source line number where this token was found
     pub line: usize,
+
+    /// The 1-based column, in characters, where this token starts
+    pub column: usize,
+
+    /// Name of the file the token was scanned from, if the scanner was
+    /// given one
+    pub file: Option<Rc<str>>,
 }
 
 /// Represents literal values in Lox source code.
 /// 
 /// Can be a number, string, boolean, or nil value.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     /// Floating-point number literal
     Number(f64),
+
+    /// Integer literal with more digits than a `Number` holds exactly
+    ///
+    /// Boxed because an inline i128 would raise the alignment, and so the
+    /// size, of every token.
+    BigInt(Box<i128>),
     
     /// String literal
     Str(String),
@@ -69,6 +98,81 @@ pub enum Literal {
     Nil,
 }
 
+impl TokenType {
+    /// Looks up the keyword spelled by a piece of text.
+    ///
+    /// This is the table the scanner uses to tell keywords from
+    /// identifiers. Matching is exact and case-sensitive.
+    ///
+    /// # Arguments
+    /// * `s` - Candidate keyword text
+    ///
+    /// # Returns
+    /// The keyword's token type, or None if the text isn't a keyword
+    ///
+    /// # Examples
+    /// ```
+    /// use jaloxc::token::TokenType;
+    ///
+    /// assert_eq!(TokenType::keyword_from_str("while"), Some(TokenType::While));
+    /// assert_eq!(TokenType::keyword_from_str("nil"), Some(TokenType::Nil));
+    /// assert_eq!(TokenType::keyword_from_str("While"), None);
+    /// assert_eq!(TokenType::keyword_from_str("whilst"), None);
+    /// assert!(TokenType::Class.is_keyword());
+    /// assert!(!TokenType::Identifier.is_keyword());
+    /// ```
+    pub fn keyword_from_str(s: &str) -> Option<TokenType> {
+        let token_type = match s {
+            "and" => TokenType::And,
+            "assert" => TokenType::Assert,
+            "break" => TokenType::Break,
+            "case" => TokenType::Case,
+            "catch" => TokenType::Catch,
+            "class" => TokenType::Class,
+            "const" => TokenType::Const,
+            "continue" => TokenType::Continue,
+            "default" => TokenType::Default,
+            "do" => TokenType::Do,
+            "else" => TokenType::Else,
+            "false" => TokenType::False,
+            "fun" => TokenType::Fun,
+            "for" => TokenType::For,
+            "if" => TokenType::If,
+            "in" => TokenType::In,
+            "nil" => TokenType::Nil,
+            "or" => TokenType::Or,
+            "print" => TokenType::Print,
+            "return" => TokenType::Return,
+            "super" => TokenType::Super,
+            "switch" => TokenType::Switch,
+            "this" => TokenType::This,
+            "try" => TokenType::Try,
+            "true" => TokenType::True,
+            "var" => TokenType::Var,
+            "while" => TokenType::While,
+            _ => return None,
+        };
+        Some(token_type)
+    }
+
+    /// Checks whether this token type is a reserved word.
+    ///
+    /// # Returns
+    /// True for keyword token types, false otherwise
+    pub fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            TokenType::And | TokenType::Assert | TokenType::Break | TokenType::Case
+                | TokenType::Catch | TokenType::Class | TokenType::Const | TokenType::Continue
+                | TokenType::Default | TokenType::Do | TokenType::Else | TokenType::False
+                | TokenType::Fun | TokenType::For | TokenType::If | TokenType::In | TokenType::Nil
+                | TokenType::Or | TokenType::Print | TokenType::Return | TokenType::Super
+                | TokenType::Switch | TokenType::This | TokenType::True | TokenType::Try
+                | TokenType::Var | TokenType::While
+        )
+    }
+}
+
 impl Token {
     /// Creates a new token instance.
     ///
@@ -77,11 +181,32 @@ impl Token {
     /// * `lexeme` - Original source text
     /// * `literal` - Optional literal value
     /// * `line` - Source line number
+    /// * `column` - 1-based source column
     ///
     /// # Returns
     /// New Token instance
-    pub fn new(token_type: TokenType, lexeme: String, literal: Option<Literal>, line: usize) -> Self {
-        Self { token_type, lexeme, literal, line }
+    pub fn new(token_type: TokenType, lexeme: impl Into<Rc<str>>, literal: Option<Literal>, line: usize, column: usize) -> Self {
+        Self { token_type, lexeme: lexeme.into(), literal, line, column, file: None }
+    }
+
+    /// Attaches the name of the file the token came from.
+    ///
+    /// # Arguments
+    /// * `file` - File name, or None if the source isn't a named file
+    ///
+    /// # Returns
+    /// The same token, tagged with the file
+    pub fn with_file(mut self, file: Option<Rc<str>>) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Checks whether this token is a reserved word.
+    ///
+    /// # Returns
+    /// True if the token's type is a keyword, false otherwise
+    pub fn is_keyword(&self) -> bool {
+        self.token_type.is_keyword()
     }
 }
 
@@ -0,0 +1,212 @@
+/// GraphViz visualisation of Lox expression trees.
+///
+/// Walks an `Expr` with a visitor and emits a `digraph` in the DOT language,
+/// giving each node a unique id and connecting parents to their children.
+use crate::expr::{Expr, LiteralValue, Visitor};
+use crate::token::Token;
+
+/// Visitor that renders an expression tree as GraphViz DOT statements.
+///
+/// Each visit method emits the statements for its node and returns the
+/// node's id, so the parent can draw an edge to it.
+#[derive(Default)]
+pub struct DotPrinter {
+    /// Id assigned to the next emitted node
+    next_id: usize,
+
+    /// DOT statements emitted so far
+    statements: Vec<String>,
+}
+
+impl DotPrinter {
+    /// Creates a new printer with no emitted nodes.
+    ///
+    /// # Returns
+    /// New DotPrinter instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders a complete DOT document for the given expression.
+    ///
+    /// # Arguments
+    /// * `expr` - Root of the expression tree to render
+    ///
+    /// # Returns
+    /// A `digraph` document that can be passed to `dot`
+    pub fn print(&mut self, expr: &Expr) -> String {
+        self.next_id = 0;
+        self.statements.clear();
+        expr.accept(self);
+
+        let mut out = String::from("digraph ast {\n");
+        for statement in &self.statements {
+            out.push_str("    ");
+            out.push_str(statement);
+            out.push('\n');
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Emits a node with the given label.
+    ///
+    /// # Arguments
+    /// * `label` - Text shown inside the node
+    ///
+    /// # Returns
+    /// The id of the new node
+    fn node(&mut self, label: &str) -> String {
+        let id = format!("node{}", self.next_id);
+        self.next_id += 1;
+        self.statements.push(format!("{} [label=\"{}\"];", id, escape(label)));
+        id
+    }
+
+    /// Emits a child subtree and an edge from the parent to it.
+    ///
+    /// # Arguments
+    /// * `parent` - Id of the parent node
+    /// * `child` - Child expression to render
+    fn edge(&mut self, parent: &str, child: &Expr) {
+        let child_id = child.accept(self);
+        self.statements.push(format!("{} -> {};", parent, child_id));
+    }
+}
+
+impl Visitor<String> for DotPrinter {
+    fn visit_binary(&mut self, left: &Expr, operator: &Token, right: &Expr) -> String {
+        let id = self.node(&operator.lexeme);
+        self.edge(&id, left);
+        self.edge(&id, right);
+        id
+    }
+
+    fn visit_grouping(&mut self, expression: &Expr) -> String {
+        let id = self.node("group");
+        self.edge(&id, expression);
+        id
+    }
+
+    fn visit_literal(&mut self, value: &LiteralValue) -> String {
+        self.node(&literal_label(value))
+    }
+
+    fn visit_unary(&mut self, operator: &Token, right: &Expr) -> String {
+        let id = self.node(&operator.lexeme);
+        self.edge(&id, right);
+        id
+    }
+
+    fn visit_ternary(&mut self, condition: &Expr, then_branch: &Expr, else_branch: &Expr) -> String {
+        let id = self.node("?:");
+        self.edge(&id, condition);
+        self.edge(&id, then_branch);
+        self.edge(&id, else_branch);
+        id
+    }
+
+    fn visit_array(&mut self, elements: &[Expr]) -> String {
+        let id = self.node("array");
+        for element in elements {
+            self.edge(&id, element);
+        }
+        id
+    }
+
+    fn visit_index(&mut self, object: &Expr, _bracket: &Token, index: &Expr) -> String {
+        let id = self.node("[]");
+        self.edge(&id, object);
+        self.edge(&id, index);
+        id
+    }
+
+    fn visit_map(&mut self, entries: &[(Expr, Expr)]) -> String {
+        let id = self.node("map");
+        for (key, value) in entries {
+            let entry = self.node(":");
+            self.statements.push(format!("{} -> {};", id, entry));
+            self.edge(&entry, key);
+            self.edge(&entry, value);
+        }
+        id
+    }
+
+    fn visit_range(&mut self, start: &Expr, end: &Expr) -> String {
+        let id = self.node("..");
+        self.edge(&id, start);
+        self.edge(&id, end);
+        id
+    }
+}
+
+/// Renders an expression tree as a GraphViz DOT document.
+///
+/// # Arguments
+/// * `expr` - Root of the expression tree to render
+///
+/// # Returns
+/// A complete `digraph` document
+///
+/// # Examples
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use jaloxc::expr::{Expr, LiteralValue};
+/// use jaloxc::token::{Token, TokenType};
+/// use jaloxc::viz::to_dot;
+///
+/// // 1 + 2
+/// let expr = Expr::binary(
+///     Expr::literal(LiteralValue::Number(1.0)),
+///     Token::new(TokenType::Plus, "+".to_string(), None, 1, 1),
+///     Expr::literal(LiteralValue::Number(2.0)),
+/// );
+///
+/// assert_eq!(to_dot(&expr), "\
+/// digraph ast {
+///     node0 [label=\"+\"];
+///     node1 [label=\"1\"];
+///     node0 -> node1;
+///     node2 [label=\"2\"];
+///     node0 -> node2;
+/// }
+/// ");
+///
+/// // An array that contains itself is labelled without recursing forever
+/// let elements = Rc::new(RefCell::new(vec![LiteralValue::String("a".to_string())]));
+/// let array = LiteralValue::Array(elements.clone());
+/// elements.borrow_mut().push(array.clone());
+/// assert!(to_dot(&Expr::literal(array)).contains("node0 [label=\"[\\\"a\\\", [...]]\"];"));
+/// ```
+pub fn to_dot(expr: &Expr) -> String {
+    DotPrinter::new().print(expr)
+}
+
+/// Builds the node label for a literal value.
+///
+/// Uses the same rendering as a printed expression tree, so an array or
+/// map that contains itself is cut off with `[...]` or `{...}`.
+///
+/// # Arguments
+/// * `value` - Literal value to describe
+///
+/// # Returns
+/// Label text, with strings quoted
+fn literal_label(value: &LiteralValue) -> String {
+    Expr::literal(value.clone()).to_string()
+}
+
+/// Escapes a label so it is valid inside a quoted DOT string.
+///
+/// # Arguments
+/// * `label` - Raw label text
+///
+/// # Returns
+/// Label with backslashes, quotes, and newlines escaped
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
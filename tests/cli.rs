@@ -0,0 +1,77 @@
+/// End-to-end tests that run the `jaloxc` binary and check its exit status
+/// and output.
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs the binary with the given arguments, feeding `stdin` to it.
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jaloxc"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start the binary");
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn valid_source_exits_with_zero() {
+    let output = run(&["--stdin"], "1 + 2");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "(+ 1 2)\n");
+}
+
+#[test]
+fn usage_error_exits_with_64() {
+    assert_eq!(run(&["--no-such-flag"], "").status.code(), Some(64));
+    assert_eq!(run(&["one.lox", "two.lox"], "").status.code(), Some(64));
+}
+
+#[test]
+fn scan_error_exits_with_65() {
+    let output = run(&["--stdin"], "1 @ 2");
+    assert_eq!(output.status.code(), Some(65));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("[line 1] Error: Unexpected character"));
+}
+
+#[test]
+fn parse_error_exits_with_65() {
+    assert_eq!(run(&["--stdin"], "1 +").status.code(), Some(65));
+    assert_eq!(run(&["--check", "-"], "(1").status.code(), Some(65));
+}
+
+#[test]
+fn missing_file_exits_with_74() {
+    let output = run(&["no/such/file.lox"], "");
+    assert_eq!(output.status.code(), Some(74));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error reading file"));
+}
+
+#[test]
+fn max_errors_caps_human_output() {
+    let output = run(&["--max-errors", "2", "--stdin"], "@ # $ ` ~");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let errors = stderr.lines().filter(|line| line.starts_with("[line")).count();
+    assert_eq!(errors, 2);
+    assert_eq!(stderr.lines().last(), Some("... and 3 more errors"));
+    assert_eq!(output.status.code(), Some(65));
+}
+
+#[test]
+fn max_errors_caps_json_output() {
+    let output = run(&["--diagnostics=json", "--max-errors", "2", "--stdin"], "@ # $ ` ~");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.lines().count(), 1);
+    assert_eq!(stderr.matches("\"severity\": \"error\"").count(), 2);
+}
+
+#[test]
+fn max_errors_zero_prints_only_the_count() {
+    for (source, summary) in [("@ #", "... and 2 more errors"), ("1 +", "... and 1 more error")] {
+        let output = run(&["--max-errors", "0", "--stdin"], source);
+        assert_eq!(String::from_utf8_lossy(&output.stderr), format!("{}\n", summary));
+        assert_eq!(output.status.code(), Some(65));
+    }
+}